@@ -0,0 +1,66 @@
+//! Compares the startup time of loading a large locale from JSON files
+//! with loading the same messages from a compiled bundle.
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use recoyx_message_localization::*;
+
+const SECTIONS: usize = 50;
+const MESSAGES_PER_SECTION: usize = 200;
+
+fn new_locator(src: &str, format: AssetFormat) -> MessageLocator {
+    MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en"])
+            .default_locale("en")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(src)
+                .base_file_names(vec!["_"])
+                .format(format)
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    )
+}
+
+/// Writes a JSON base file of `SECTIONS` sections of `MESSAGES_PER_SECTION`
+/// messages each, along with its compiled bundle, and returns their directory.
+fn write_assets(runtime: &tokio::runtime::Runtime) -> String {
+    let dir = std::env::temp_dir().join(format!("recoyx_message_localization_bench_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("en")).unwrap();
+    let mut root = serde_json::Map::new();
+    for section in 0..SECTIONS {
+        let mut messages = serde_json::Map::new();
+        for message in 0..MESSAGES_PER_SECTION {
+            messages.insert(format!("message_{}", message), serde_json::json!(format!("Message {} of section {}, for $name", message, section)));
+        }
+        root.insert(format!("section_{}", section), serde_json::Value::Object(messages));
+    }
+    std::fs::write(dir.join("en/_.json"), serde_json::to_string(&root).unwrap()).unwrap();
+
+    let src = dir.to_str().unwrap().to_string();
+    let mut msg_locator = new_locator(&src, AssetFormat::Json);
+    assert!(runtime.block_on(msg_locator.load(None)));
+    std::fs::write(dir.join("en.bundle"), msg_locator.compile_bundle(&parse_locale("en").unwrap())).unwrap();
+    src
+}
+
+fn startup(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let src = write_assets(&runtime);
+    let mut group = c.benchmark_group("startup");
+    for (name, format) in [("json", AssetFormat::Json), ("bundle", AssetFormat::Bundle)] {
+        // the locator is created outside the measurement, which covers loading only
+        group.bench_function(name, |b| b.to_async(&runtime).iter_batched(
+            || new_locator(&src, format),
+            |mut msg_locator| async move {
+                assert!(msg_locator.load(None).await);
+                msg_locator
+            },
+            BatchSize::SmallInput,
+        ));
+    }
+    group.finish();
+    let _ = std::fs::remove_dir_all(&src);
+}
+
+criterion_group!(benches, startup);
+criterion_main!(benches);
@@ -0,0 +1,78 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Lit, Meta, NestedMeta};
+
+/// Derives `LocalizedEnum` for an enum. Each variant maps to
+/// its name in snake case, optionally preceded by a prefix given
+/// through `#[localized_enum(prefix = "...")]`.
+#[proc_macro_derive(LocalizedEnum, attributes(localized_enum))]
+pub fn derive_localized_enum(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let data = match &input.data {
+        Data::Enum(data) => data,
+        _ => return syn::Error::new_spanned(name, "LocalizedEnum can only be derived for enums").to_compile_error().into(),
+    };
+
+    let mut prefix: Option<String> = None;
+    for attr in input.attrs.iter().filter(|a| a.path.is_ident("localized_enum")) {
+        let meta = match attr.parse_meta() {
+            Ok(meta) => meta,
+            Err(e) => return e.to_compile_error().into(),
+        };
+        if let Meta::List(list) = meta {
+            for nested in list.nested.iter() {
+                match nested {
+                    NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("prefix") => {
+                        if let Lit::Str(s) = &nv.lit {
+                            prefix = Some(s.value());
+                        } else {
+                            return syn::Error::new_spanned(&nv.lit, "expected a string literal").to_compile_error().into();
+                        }
+                    },
+                    _ => return syn::Error::new_spanned(nested, "unknown localized_enum attribute").to_compile_error().into(),
+                }
+            }
+        }
+    }
+
+    let arms = data.variants.iter().map(|variant| {
+        let ident = &variant.ident;
+        let key = match &prefix {
+            Some(prefix) => format!("{}.{}", prefix, to_snake_case(&ident.to_string())),
+            None => to_snake_case(&ident.to_string()),
+        };
+        quote! { Self::#ident { .. } => #key, }
+    });
+
+    let expanded = quote! {
+        impl #impl_generics ::recoyx_message_localization::LocalizedEnum for #name #ty_generics #where_clause {
+            fn message_key(&self) -> &'static str {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+    };
+    expanded.into()
+}
+
+/// Converts a variant name to snake case, keeping runs of capitals,
+/// such as acronyms, as a single word (`HTTPError` becomes `http_error`).
+fn to_snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut r = String::new();
+    for (i, &ch) in chars.iter().enumerate() {
+        if ch.is_uppercase() {
+            let starts_word = i != 0 && (!chars[i - 1].is_uppercase()
+                || matches!(chars.get(i + 1), Some(next) if next.is_lowercase()));
+            if starts_word && chars[i - 1] != '_' { r.push('_'); }
+            r.extend(ch.to_lowercase());
+        } else {
+            r.push(ch);
+        }
+    }
+    r
+}
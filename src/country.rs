@@ -21,6 +21,28 @@ impl Display for Country {
     }
 }
 
+/// Returns every ISO 3166-1 country, sorted by alpha-2 code.
+pub fn all_countries() -> Vec<Country> {
+    isocountry::CountryCode::as_array_alpha2().iter().map(|code| Country { _standard_code: *code }).collect()
+}
+
+/// Serializes as the ISO 3166-1 alpha-2 code, such as `"BR"`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Country {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self._standard_code.alpha2())
+    }
+}
+
+/// Deserializes from a country code as accepted by `parse_country`.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Country {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let src = String::deserialize(deserializer)?;
+        parse_country(&src).map_err(|_| serde::de::Error::custom(format!("invalid country code {}", src)))
+    }
+}
+
 pub fn parse_country<S: ToString>(src: S) -> Result<Country, isocountry::CountryCodeParseErr> {
     let src = src.to_string();
     let src: &str = src.as_ref();
@@ -0,0 +1,67 @@
+use super::{Locale, MessageLocatorFormatArgument};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone};
+use std::fmt::Display;
+
+/// Formatting argument carrying a date and time in a specific timezone.
+/// It is substituted into the `$name` placeholder, formatted according
+/// to the current locale's conventions and in the timezone of the given
+/// value, so daylight saving time is taken into account.
+///
+/// ```ignore
+/// let starts = Utc::now().with_timezone(&chrono_tz::America::Sao_Paulo);
+/// msg_locator.get_formatted("event.starts", vec![&DateTimeArg::new("date", starts)]);
+/// ```
+pub struct DateTimeArg<Tz: TimeZone> {
+    name: String,
+    value: DateTime<Tz>,
+}
+
+impl<Tz: TimeZone> DateTimeArg<Tz> {
+    pub fn new<S: ToString>(name: S, value: DateTime<Tz>) -> Self {
+        Self { name: name.to_string(), value }
+    }
+}
+
+impl<Tz: TimeZone> MessageLocatorFormatArgument for DateTimeArg<Tz> where Tz::Offset: Display {
+    fn as_localized_var(&self, locale: &Locale) -> Option<(String, String)> {
+        let (date, time) = date_and_time_patterns(locale);
+        Some((self.name.clone(), self.value.format(&format!("{} {}", date, time)).to_string()))
+    }
+}
+
+/// Formats an ISO 8601 date, such as `2021-03-09`, or date and time,
+/// such as `2021-03-09T14:30` or `2021-03-09T14:30:00-03:00`, in the
+/// conventions of the locale, as `DateTimeArg` does. `style` is `date`
+/// or `time` for only that part, or `datetime` or `None` for both.
+/// Returns `None` if the value does not parse or lacks the requested part.
+pub(crate) fn format_date_str(value: &str, style: Option<&str>, locale: &Locale) -> Option<String> {
+    let value = value.trim();
+    let (date, time) = if let Ok(value) = DateTime::parse_from_rfc3339(value) {
+        (value.date_naive(), Some(value.time()))
+    } else if let Ok(value) = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S").or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M")) {
+        (value.date(), Some(value.time()))
+    } else {
+        (NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?, None)
+    };
+    let (date_pattern, time_pattern) = date_and_time_patterns(locale);
+    match (style.unwrap_or("datetime"), time) {
+        ("datetime", Some(time)) => Some(date.and_time(time).format(&format!("{} {}", date_pattern, time_pattern)).to_string()),
+        ("datetime", None) | ("date", _) => Some(date.format(date_pattern).to_string()),
+        ("time", Some(time)) => Some(time.format(time_pattern).to_string()),
+        _ => None,
+    }
+}
+
+fn date_and_time_patterns(locale: &Locale) -> (&'static str, &'static str) {
+    let language = locale.language();
+    let region = locale.standard_tag().get_region().map(|r| r.to_string().to_uppercase());
+    if language == "en" && (region.is_none() || region.as_deref() == Some("US")) {
+        return ("%m/%d/%Y", "%I:%M %p");
+    }
+    match language.as_ref() {
+        "ja" | "zh" | "hu" | "lt" => ("%Y/%m/%d", "%H:%M"),
+        "ko" => ("%Y. %m. %d.", "%H:%M"),
+        "de" | "ru" | "uk" | "pl" | "fi" | "cs" | "sk" | "nb" | "no" | "da" | "tr" | "ro" => ("%d.%m.%Y", "%H:%M"),
+        _ => ("%d/%m/%Y", "%H:%M"),
+    }
+}
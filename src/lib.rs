@@ -1,3 +1,7 @@
+mod shared;
+use shared::Shared;
+pub use shared::MaybeSendSync;
+
 mod locale_basic_data;
 use locale_basic_data::{
     LOCALE_BASIC_DATA, LocaleBasicData,
@@ -5,13 +9,36 @@ use locale_basic_data::{
 pub use locale_basic_data::Direction;
 
 mod locale;
-pub use locale::{Locale, parse_locale};
+pub use locale::{Locale, parse_locale, negotiate_locale, system_locale, locale_from_env, all_locales};
 
 mod country;
-pub use country::{Country, parse_country};
+pub use country::{Country, parse_country, all_countries};
+
+mod localized_enum;
+pub use localized_enum::LocalizedEnum;
+pub use recoyx_message_localization_derive::LocalizedEnum;
+
+mod localizer;
+pub use localizer::Localizer;
+
+mod message_syntax;
+mod plural_rules;
+mod number_format;
+pub use number_format::{LocalizedNumber, NumberArg};
 
 mod message_locator;
 pub use message_locator::{
     MessageLocator, MessageLocatorOptions, MessageLocatorAssetOptions,
-    MessageLocatorLoadVia, MessageLocatorFormatArgument,
-};
\ No newline at end of file
+    MessageLocatorLoadVia, MessageLocatorFormatArgument, MessageFormatter, AssetFormat, MergeStrategy, Compression,
+    AssetDiff, diff_assets, apply_patch, FallbackProvider, AssetLoader,
+    PreflightIssue, PreflightProblem, MessageLoadError, ConfigError, PlaceholderStyle,
+    ResolutionTrace, ResolutionStep,
+};
+
+mod shared_locator;
+pub use shared_locator::SharedMessageLocator;
+
+#[cfg(feature = "datetime")]
+mod datetime;
+#[cfg(feature = "datetime")]
+pub use datetime::DateTimeArg;
\ No newline at end of file
@@ -1,18 +1,23 @@
 use super::{
     LocaleBasicData, Direction, Country,
-    LOCALE_BASIC_DATA,
+    LOCALE_BASIC_DATA, Shared,
 };
-use std::{fmt::{Display, Formatter}, hash::{Hash, Hasher}, rc::Rc, str::FromStr};
+use std::{collections::HashSet, fmt::{Display, Formatter}, hash::{Hash, Hasher}, str::FromStr};
 use language_tag::LangTag;
 
 /// Parses a locale code. If the given string is a valid language tag but its
 /// language subtag is not a known language, an error is returned instead.
 ///
+/// Surrounding whitespace is ignored and the casing of subtags is normalized
+/// per BCP 47 conventions: lowercase language, titlecase script and uppercase
+/// region, so that `" En-us "` parses as `en-US`. Underscores are accepted
+/// as separators.
+///
 /// Some region codes are specially translated into the correct language identifier,
 /// such as from `jp` to `ja` and `br` to `pt-BR`.
 ///
 pub fn parse_locale<S: ToString>(src: S) -> Result<Locale, String> {
-    let src = src.to_string().replace("_", "-");
+    let src = normalize_tag(&src.to_string().trim().replace("_", "-"));
     let src: &str = src.as_ref();
     let tag = LangTag::from_str(src);
     if tag.is_err() {
@@ -26,16 +31,130 @@ pub fn parse_locale<S: ToString>(src: S) -> Result<Locale, String> {
         if src == "jp" || src == "jpn" { tag = LangTag::from_str("ja").unwrap(); }
     }
     if LOCALE_BASIC_DATA.get(&tag.get_language().to_string().replace("-", "")).is_none() {
-        return Err(String::from("Invalid locale code."));
+        return Err(format!("Unknown language in locale code {}.", src));
     }
     Ok(Locale {
-        _tag: Rc::new(tag),
+        _tag: Shared::new(tag),
     })
 }
 
+/// Normalizes the casing of a language tag's subtags. Subtags after
+/// a singleton, such as the `u` of a Unicode extension, are lowercased.
+fn normalize_tag(src: &str) -> String {
+    let mut extension = false;
+    let subtags: Vec<String> = src.split('-').enumerate().map(|(i, subtag)| {
+        extension = extension || (i != 0 && subtag.len() == 1);
+        match subtag.len() {
+            _ if i == 0 || extension || !subtag.is_ascii() => subtag.to_lowercase(),
+            2 => subtag.to_uppercase(),
+            4 => subtag[..1].to_uppercase() + &subtag[1..].to_lowercase(),
+            _ => subtag.to_lowercase(),
+        }
+    }).collect();
+    subtags.join("-")
+}
+
+/// Returns a locale for every language known to the crate, such as
+/// for a language picker, sorted by tag. The locales have no region;
+/// their display names are their native language names.
+pub fn all_locales() -> Vec<Locale> {
+    let mut r: Vec<Locale> = LOCALE_BASIC_DATA.keys().filter_map(|code| parse_locale(code).ok()).collect();
+    r.sort_by_key(|locale| locale.standard_tag().to_string());
+    r
+}
+
+/// Selects the supported locale that best matches an `Accept-Language`
+/// header value, such as `en-US,en;q=0.9,pt;q=0.8`. Entries are tried in
+/// descending order of quality; each matches a supported locale exactly or,
+/// failing that, by language, preferring the language-only locale (`pt`
+/// matches `pt` before `pt-BR`). Entries with quality zero, the `*` wildcard
+/// and invalid tags are ignored. If nothing matches, `default` is returned.
+pub fn negotiate_locale(accept_language: &str, supported: &HashSet<Locale>, default: &Locale) -> Locale {
+    let mut entries: Vec<(f32, Locale)> = vec![];
+    for entry in accept_language.split(',') {
+        let mut params = entry.split(';');
+        let tag = params.next().unwrap_or("").trim();
+        let quality = params
+            .filter_map(|param| param.trim().strip_prefix("q="))
+            .next()
+            .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok());
+        let quality = match quality {
+            Some(quality) if quality > 0.0 => quality,
+            _ => continue,
+        };
+        if tag.is_empty() || tag == "*" {
+            continue;
+        }
+        if let Ok(locale) = parse_locale(tag) {
+            entries.push((quality, locale));
+        }
+    }
+    entries.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+    for (_, locale) in entries {
+        if supported.contains(&locale) {
+            return locale;
+        }
+        let language = locale.language();
+        let mut candidates: Vec<&Locale> = supported.iter().filter(|l| l.language() == language).collect();
+        candidates.sort_by_key(|l| (l.standard_tag().get_region().is_some(), l.standard_tag().to_string()));
+        if let Some(candidate) = candidates.first() {
+            return (*candidate).clone();
+        }
+    }
+    default.clone()
+}
+
+/// Detects the locale of the operating system's user interface: from the
+/// `LC_ALL`, `LC_MESSAGES` or `LANG` environment variables on Unix, in that
+/// order, or from `GetUserDefaultLocaleName` on Windows. Encodings and
+/// modifiers, as in `pt_BR.UTF-8@euro`, are ignored. Returns `None` if
+/// detection fails, the locale is `C` or `POSIX`, or it does not parse.
+///
+/// The result may not be supported by a `MessageLocator`; check it with
+/// `MessageLocator::supports_locale` before loading it.
+pub fn system_locale() -> Option<Locale> {
+    #[cfg(not(windows))]
+    let name = env_locale_name(|var| std::env::var(var).ok())?;
+    #[cfg(windows)]
+    let name = system_locale_name()?;
+    parse_system_locale_name(&name)
+}
+
+/// Detects a locale from environment variables as `system_locale` does on Unix,
+/// reading each variable through `var`, which returns `None` if it is not set.
+/// This allows detecting the locale of another process's environment,
+/// or of a test's, without changing the environment of this process.
+pub fn locale_from_env<F: Fn(&str) -> Option<String>>(var: F) -> Option<Locale> {
+    parse_system_locale_name(&env_locale_name(var)?)
+}
+
+fn env_locale_name<F: Fn(&str) -> Option<String>>(var: F) -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"].iter()
+        .filter_map(|name| var(name))
+        .find(|value| !value.is_empty())
+}
+
+fn parse_system_locale_name(name: &str) -> Option<Locale> {
+    let name = name.split(['.', '@']).next().unwrap_or("");
+    if name.is_empty() || name == "C" || name == "POSIX" {
+        return None;
+    }
+    parse_locale(name).ok()
+}
+
+#[cfg(windows)]
+fn system_locale_name() -> Option<String> {
+    let mut buffer = [0u16; 85];
+    let len = unsafe { windows_sys::Win32::Globalization::GetUserDefaultLocaleName(buffer.as_mut_ptr(), buffer.len() as i32) };
+    if len <= 1 {
+        return None;
+    }
+    String::from_utf16(&buffer[..len as usize - 1]).ok()
+}
+
 #[derive(Clone, Eq)]
 pub struct Locale {
-    pub(crate) _tag: Rc<LangTag>,
+    pub(crate) _tag: Shared<LangTag>,
 }
 
 impl Locale {
@@ -74,6 +193,26 @@ impl Locale {
         None
     }
 
+    /// Returns the display name of this locale as rendered in `target`,
+    /// such as `Portuguese (Brazil)` for `pt-BR` in English. The crate's data
+    /// holds English and native language names only, so for any target
+    /// language other than English, the native display name is returned,
+    /// as by `to_string`. Country names are always in English.
+    pub fn display_name_in(&self, target: &Locale) -> String {
+        if target.language() != "en" || self.international_name().is_empty() {
+            return self.to_string();
+        }
+        match self.country() {
+            Some(country) => format!("{} ({})", self.international_name(), country.international_name()),
+            None => self.international_name().to_string(),
+        }
+    }
+
+    /// Returns the language subtag in lowercase, such as `en` for `en-US`.
+    pub fn language(&self) -> String {
+        self._tag.get_language().to_string().replace("-", "").to_lowercase()
+    }
+
     pub fn standard_tag(&self) -> &LangTag {
         self._tag.as_ref()
     }
@@ -98,4 +237,21 @@ impl Hash for Locale {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self._tag.to_string().hash(state);
     }
+}
+
+/// Serializes as the standard tag, such as `"pt-BR"`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Locale {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.standard_tag().to_string())
+    }
+}
+
+/// Deserializes from a locale code as accepted by `parse_locale`.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Locale {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let src = String::deserialize(deserializer)?;
+        parse_locale(&src).map_err(|error| serde::de::Error::custom(format!("invalid locale {}: {}", src, error)))
+    }
 }
\ No newline at end of file
@@ -0,0 +1,23 @@
+/// Maps enum variants to message identifiers.
+///
+/// This trait is usually derived. Each variant maps to its name in snake case,
+/// optionally preceded by a prefix:
+///
+/// ```
+/// use recoyx_message_localization::LocalizedEnum;
+///
+/// #[derive(LocalizedEnum)]
+/// #[localized_enum(prefix = "status")]
+/// enum OrderStatus {
+///     Pending,
+///     InTransit,
+///     Delivered { signed_by: String },
+/// }
+///
+/// assert_eq!(OrderStatus::Pending.message_key(), "status.pending");
+/// assert_eq!(OrderStatus::InTransit.message_key(), "status.in_transit");
+/// ```
+pub trait LocalizedEnum {
+    /// Returns the dotted message identifier for this variant.
+    fn message_key(&self) -> &'static str;
+}
@@ -0,0 +1,47 @@
+use super::{Locale, MessageLocator};
+use std::collections::HashMap;
+
+/// An object-safe interface to message lookup, so that a `MessageLocator`
+/// can be held as a `Box<dyn Localizer>` and swapped for another
+/// implementation, such as a mock in tests.
+pub trait Localizer {
+    /// Returns the currently loaded locale.
+    fn current_locale(&self) -> Option<Locale>;
+
+    /// Retrieves message by identifier, or the identifier if it is missing.
+    fn get(&self, id: &str) -> String;
+
+    /// Retrieves message by identifier with variables,
+    /// or the identifier if it is missing.
+    fn get_formatted(&self, id: &str, vars: &HashMap<String, String>) -> String;
+
+    /// Retrieves message by identifier with variables,
+    /// or `None` if it is missing.
+    fn get_opt(&self, id: &str, vars: &HashMap<String, String>) -> Option<String>;
+
+    /// Retrieves the plural form of a message for a count,
+    /// or the identifier if it is missing.
+    fn get_plural(&self, id: &str, count: i64, vars: &HashMap<String, String>) -> String;
+}
+
+impl Localizer for MessageLocator {
+    fn current_locale(&self) -> Option<Locale> {
+        MessageLocator::current_locale(self)
+    }
+
+    fn get(&self, id: &str) -> String {
+        MessageLocator::get(self, id)
+    }
+
+    fn get_formatted(&self, id: &str, vars: &HashMap<String, String>) -> String {
+        MessageLocator::get_formatted(self, id, vec![vars])
+    }
+
+    fn get_opt(&self, id: &str, vars: &HashMap<String, String>) -> Option<String> {
+        MessageLocator::get_opt(self, id, vec![vars])
+    }
+
+    fn get_plural(&self, id: &str, count: i64, vars: &HashMap<String, String>) -> String {
+        MessageLocator::get_plural(self, id, count, vec![vars])
+    }
+}
@@ -1,8 +1,12 @@
-use std::{cell::{Cell, RefCell}, collections::{HashMap, HashSet}, rc::Rc};
+use std::{cell::{Cell, RefCell}, collections::{HashMap, HashSet}, sync::{Arc, Mutex}};
 use super::*;
 use maplit::{hashmap, hashset};
 use lazy_static::lazy_static;
 use lazy_regex::regex;
+use async_trait::async_trait;
+use futures::future::{try_join, try_join_all};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_normalization::{UnicodeNormalization, char::is_combining_mark};
 
 /// Creates a `HashMap<String, String>` from a list of key-value pairs.
 /// This is based on the [`maplit`](https://github.com/bluss/maplit) crate.
@@ -42,44 +46,167 @@ macro_rules! localization_vars {
 /// Flexible locale mapping with support for loading message resources.
 pub struct MessageLocator {
     _current_locale: Option<Locale>,
-    _locale_path_components: Rc<HashMap<Locale, String>>,
-    _supported_locales: Rc<HashSet<Locale>>,
+    _locale_path_components: Shared<HashMap<Locale, String>>,
+    _supported_locales: Shared<HashSet<Locale>>,
     _default_locale: Locale,
-    _fallbacks: Rc<HashMap<Locale, Vec<Locale>>>,
-    _assets: Rc<HashMap<Locale, serde_json::Value>>,
+    _fallbacks: Shared<HashMap<Locale, Vec<Locale>>>,
+    _aliases: Shared<HashMap<String, Locale>>,
+    _assets: Shared<HashMap<Locale, serde_json::Value>>,
+    /// Locales whose assets hold only the base files given to `load_namespaces`.
+    _partial_locales: HashSet<Locale>,
     _assets_src: String,
     _assets_base_file_names: Vec<String>,
     _assets_clean_unused: bool,
     _assets_load_via: MessageLocatorLoadVia,
+    _assets_memory: Shared<HashMap<String, serde_json::Value>>,
+    _assets_file_names: Shared<HashMap<(String, String), String>>,
+    _assets_static: Shared<HashMap<(String, String), &'static str>>,
+    _assets_loader: Option<Arc<dyn AssetLoader>>,
+    _assets_shared_base_file_names: Vec<String>,
+    _assets_shared_src: String,
+    _assets_format: AssetFormat,
+    _assets_inherit_language_files: bool,
+    _assets_front_matter: bool,
+    _assets_merge_strategy: MergeStrategy,
+    _assets_single_file: bool,
+    _assets_compression: Compression,
+    _assets_response_transform: Option<ResponseTransform>,
+    _http_client: reqwest::Client,
+    _http_retries: u32,
+    _http_retry_backoff: std::time::Duration,
+    _http_cache_dir: Option<std::path::PathBuf>,
+    _reload_interval: Option<std::time::Duration>,
+    _case_insensitive_keys: bool,
+    _implicit_language_fallback: bool,
+    _placeholder_style: PlaceholderStyle,
+    _max_format_steps: usize,
+    _bidi_isolation: bool,
+    _recursive_interpolation: bool,
+    _id_separator: char,
+    _declension_tables: Vec<String>,
+    _key_sources: Option<Shared<HashMap<Locale, HashMap<String, String>>>>,
+    _formatters: Shared<HashMap<String, MessageFormatter>>,
+    _falsy_values: Shared<HashSet<String>>,
+    _fallback_provider: Option<Arc<dyn FallbackProvider>>,
+    _on_missing: Option<MissingHandler>,
+    _provided_messages: Shared<Mutex<ProvidedMessages>>,
+    _direction_override: Option<Direction>,
+    _locale_change_listeners: Vec<LocaleChangeListener>,
 }
 
 impl MessageLocator {
     /// Constructs a `MessageLocator` object.
+    ///
+    /// Panics if the options are invalid; use `MessageLocatorOptions::build`
+    /// to handle the error.
     pub fn new(options: &MessageLocatorOptions) -> Self {
+        Self::from_options(options).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    fn from_options(options: &MessageLocatorOptions) -> Result<Self, ConfigError> {
+        let parse = |code: &String, setting: &'static str| parse_locale(code).map_err(|reason| ConfigError::InvalidLocale {
+            setting,
+            locale: code.clone(),
+            reason,
+        });
         let mut locale_path_components = HashMap::<Locale, String>::new();
         let mut supported_locales = HashSet::<Locale>::new();
         for code in options._supported_locales.borrow().iter() {
-            let locale_parse = parse_locale(code).unwrap();
+            let locale_parse = parse(code, "supported_locales")?;
             locale_path_components.insert(locale_parse.clone(), code.clone());
             supported_locales.insert(locale_parse);
         }
+        let default_locale = parse(&options._default_locale.borrow(), "default_locale")?;
         let mut fallbacks = HashMap::<Locale, Vec<Locale>>::new();
         for (k, v) in options._fallbacks.borrow().iter() {
-            fallbacks.insert(parse_locale(k).unwrap(), v.iter().map(|s| parse_locale(s).unwrap()).collect());
+            fallbacks.insert(parse(k, "fallbacks")?, v.iter().map(|s| parse(s, "fallbacks")).collect::<Result<_, _>>()?);
         }
-        let default_locale = options._default_locale.borrow().clone();
-        Self {
+        let mut unsupported_fallbacks: Vec<(String, String)> = fallbacks.iter()
+            .flat_map(|(locale, list)| list.iter().map(move |fallback| (locale, fallback)))
+            .filter(|(_, fallback)| !supported_locales.contains(*fallback))
+            .map(|(locale, fallback)| (locale.standard_tag().to_string(), fallback.standard_tag().to_string()))
+            .collect();
+        if !unsupported_fallbacks.is_empty() {
+            unsupported_fallbacks.sort();
+            return Err(ConfigError::UnsupportedFallbacks { entries: unsupported_fallbacks });
+        }
+        let mut aliases = HashMap::<String, Locale>::new();
+        for (alias, code) in options._aliases.borrow().iter() {
+            aliases.insert(alias.replace('_', "-").to_lowercase(), parse(code, "aliases")?);
+        }
+        for (code, dir_name) in options._assets.borrow()._path_overrides.borrow().iter() {
+            locale_path_components.insert(parse(code, "path_for")?, dir_name.clone());
+        }
+        let mut file_names = HashMap::<(String, String), String>::new();
+        for ((code, base_name), file_name) in options._assets.borrow()._file_name_overrides.borrow().iter() {
+            let path_comp = locale_path_components.get(&parse(code, "file_name_for")?).cloned().unwrap_or_else(|| code.clone());
+            file_names.insert((path_comp, base_name.clone()), file_name.clone());
+        }
+        let mut http_headers = reqwest::header::HeaderMap::new();
+        for (name, value) in options._assets.borrow()._http_headers.borrow().iter() {
+            let invalid_header = || ConfigError::InvalidHttpHeader { name: name.clone() };
+            let header_name = reqwest::header::HeaderName::from_bytes(name.as_bytes()).map_err(|_| invalid_header())?;
+            let header_value = reqwest::header::HeaderValue::from_str(value).map_err(|_| invalid_header())?;
+            http_headers.insert(header_name, header_value);
+        }
+        #[allow(unused_mut)]
+        let mut http_client = reqwest::Client::builder().default_headers(http_headers);
+        // the wasm client leaves content encoding to the browser and has no client timeout
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            http_client = http_client.gzip(options._assets.borrow()._compression.get() == Compression::Gzip);
+            if let Some(timeout) = options._assets.borrow()._http_timeout.get() {
+                http_client = http_client.timeout(timeout);
+            }
+        }
+        Ok(Self {
             _current_locale: None,
-            _locale_path_components: Rc::new(locale_path_components),
-            _supported_locales: Rc::new(supported_locales),
-            _default_locale: parse_locale(&default_locale).unwrap(),
-            _fallbacks: Rc::new(fallbacks),
-            _assets: Rc::new(HashMap::new()),
+            _locale_path_components: Shared::new(locale_path_components),
+            _supported_locales: Shared::new(supported_locales),
+            _default_locale: default_locale,
+            _fallbacks: Shared::new(fallbacks),
+            _aliases: Shared::new(aliases),
+            _assets: Shared::new(HashMap::new()),
+            _partial_locales: HashSet::new(),
             _assets_src: options._assets.borrow()._src.borrow().clone(),
             _assets_base_file_names: options._assets.borrow()._base_file_names.borrow().iter().map(|s| s.clone()).collect(),
             _assets_clean_unused: options._assets.borrow()._clean_unused.get(),
             _assets_load_via: options._assets.borrow()._load_via.get(),
-        }
+            _assets_memory: Shared::new(options._assets.borrow()._memory.borrow().clone()),
+            _assets_file_names: Shared::new(file_names),
+            _assets_static: Shared::new(options._assets.borrow()._static_assets.borrow().clone()),
+            _assets_loader: options._assets.borrow()._loader.borrow().clone(),
+            _assets_shared_base_file_names: options._assets.borrow()._shared_base_file_names.borrow().clone(),
+            _assets_shared_src: options._assets.borrow()._shared_src.borrow().clone(),
+            _assets_format: options._assets.borrow()._format.get(),
+            _assets_inherit_language_files: options._assets.borrow()._inherit_language_files.get(),
+            _assets_front_matter: options._assets.borrow()._front_matter.get(),
+            _assets_merge_strategy: options._assets.borrow()._merge_strategy.get(),
+            _assets_single_file: options._assets.borrow()._single_file.get(),
+            _assets_compression: options._assets.borrow()._compression.get(),
+            _assets_response_transform: options._assets.borrow()._response_transform.borrow().clone(),
+            _http_client: http_client.build().unwrap(),
+            _http_retries: options._assets.borrow()._http_retries.get(),
+            _http_retry_backoff: options._assets.borrow()._http_retry_backoff.get(),
+            _http_cache_dir: options._assets.borrow()._http_cache_dir.borrow().clone(),
+            _reload_interval: options._assets.borrow()._reload_interval.get(),
+            _case_insensitive_keys: options._case_insensitive_keys.get(),
+            _implicit_language_fallback: options._implicit_language_fallback.get(),
+            _placeholder_style: options._placeholder_style.get(),
+            _max_format_steps: options._max_format_steps.get(),
+            _bidi_isolation: options._bidi_isolation.get(),
+            _recursive_interpolation: options._recursive_interpolation.get(),
+            _id_separator: options._id_separator.get(),
+            _declension_tables: options._declension_tables.borrow().split('/').map(|s| s.to_string()).collect(),
+            _key_sources: if options._track_key_sources.get() { Some(Shared::new(HashMap::new())) } else { None },
+            _formatters: Shared::new(options._formatters.borrow().clone()),
+            _falsy_values: Shared::new(options._falsy_values.borrow().iter().map(|s| s.trim().to_lowercase()).collect()),
+            _fallback_provider: options._fallback_provider.borrow().clone(),
+            _on_missing: options._on_missing.borrow().clone(),
+            _provided_messages: Shared::new(Mutex::new(HashMap::new())),
+            _direction_override: None,
+            _locale_change_listeners: vec![],
+        })
     }
 
     /// Returns a set of supported locale codes, reflecting
@@ -88,11 +215,50 @@ impl MessageLocator {
         self._supported_locales.as_ref().clone()
     }
 
-    /// Returns `true` if the locale is one of the supported locales
-    /// that were specified when constructing the `MessageLocator`,
-    /// otherwise `false`.
+    /// Returns `true` if the locale, or the locale it is an alias of,
+    /// is one of the supported locales that were specified when constructing
+    /// the `MessageLocator`, otherwise `false`.
     pub fn supports_locale(&self, arg: &Locale) -> bool {
-        self._supported_locales.contains(arg)
+        self._supported_locales.contains(&self.canonical_locale(arg))
+    }
+
+    /// Returns the locale that `locale` is an alias of, as given to
+    /// `MessageLocatorOptions::aliases`, or `locale` itself.
+    fn canonical_locale(&self, locale: &Locale) -> Locale {
+        self._aliases.get(&locale.standard_tag().to_string().to_lowercase()).cloned().unwrap_or_else(|| locale.clone())
+    }
+
+    /// Finds the supported locale closest to a locale code from loose input,
+    /// such as `EN_us`: case and `_` separators are normalized before parsing.
+    /// If the locale itself is not supported, the first supported locale of
+    /// the same language is returned, as ordered by `variants_of`, so that
+    /// `en-AU` matches `en` before `en-GB`. Returns `None` if the code does not
+    /// parse or no locale of its language is supported. Aliases given to
+    /// `MessageLocatorOptions::aliases` are resolved first, including
+    /// ones that are not valid locale codes by themselves.
+    ///
+    /// Unlike `supports_locale`, this is meant for client-provided codes.
+    pub fn match_supported(&self, requested: &str) -> Option<Locale> {
+        if let Some(locale) = self._aliases.get(&requested.trim().replace('_', "-").to_lowercase()) {
+            return Some(locale.clone());
+        }
+        let locale = self.canonical_locale(&parse_locale(requested).ok()?);
+        if self.supports_locale(&locale) {
+            return Some(locale);
+        }
+        self.variants_of(&locale.language()).into_iter().next()
+    }
+
+    /// Returns the supported locales whose language subtag is `language`,
+    /// ignoring case, sorted by tag; for example, `en`, `en-GB` and `en-US`
+    /// for `"en"`. This suits a two-level language picker.
+    pub fn variants_of(&self, language: &str) -> Vec<Locale> {
+        let language = language.to_lowercase();
+        let mut r: Vec<Locale> = self._supported_locales.iter()
+            .filter(|locale| locale.language() == language)
+            .cloned().collect();
+        r.sort_by_key(|locale| locale.standard_tag().to_string());
+        r
     }
 
     /// Returns the currently loaded locale.
@@ -100,224 +266,2207 @@ impl MessageLocator {
         self._current_locale.clone()
     }
 
+    /// Returns the text direction of the currently loaded locale, or of the
+    /// default locale if no locale is loaded.
+    pub fn direction(&self) -> Direction {
+        self._current_locale.as_ref().unwrap_or(&self._default_locale).direction()
+    }
+
+    /// Returns `direction()`, unless a direction override is set
+    /// through `set_direction_override`, which is returned instead.
+    pub fn current_direction(&self) -> Direction {
+        self._direction_override.unwrap_or_else(|| self.direction())
+    }
+
+    /// Forces the direction returned by `current_direction` regardless of
+    /// the locale's natural direction, such as for testing right-to-left layout
+    /// with a left-to-right language. `None` restores the natural direction.
+    pub fn set_direction_override(&mut self, direction: Option<Direction>) {
+        self._direction_override = direction;
+    }
+
     /// Returns the currently loaded locale followed by its fallbacks or empty if no locale is loaded.
     pub fn current_locale_seq(&self) -> HashSet<Locale> {
-        if let Some(c) = self.current_locale() {
-            let mut r: HashSet<Locale> = hashset![c.clone()];
-            self.enumerate_fallbacks(c.clone(), &mut r);
-            return r;
+        self.current_locale_seq_ordered().into_iter().collect()
+    }
+
+    /// Returns the currently loaded locale followed by its fallbacks in the
+    /// order messages are searched, depth-first and without duplicates,
+    /// or empty if no locale is loaded.
+    pub fn current_locale_seq_ordered(&self) -> Vec<Locale> {
+        match self._current_locale.as_ref() {
+            Some(locale) => self.lookup_chain(locale),
+            None => vec![],
         }
-        hashset![]
     }
 
     /// Attempts to load the specified locale and its fallbacks.
-    /// If any resource fails to load, the method returns `false`, otherwise `true`.
+    /// If the locale is not supported or any resource fails to load,
+    /// the method returns `false`, otherwise `true`.
     pub async fn update_locale(&mut self, new_locale: Locale) -> bool {
         self.load(Some(new_locale)).await
     }
 
     /// Attempts to load a locale and its fallbacks.
     /// If the locale argument is specified, it is loaded.
-    /// Otherwise, if there is a default locale, it is loaded, and if not,
-    /// the method panics.
+    /// Otherwise, the default locale is loaded.
     ///
-    /// If any resource fails to load, the method returns `false`, otherwise `true`.
-    pub async fn load(&mut self, mut new_locale: Option<Locale>) -> bool {
-        if new_locale.is_none() { new_locale = Some(self._default_locale.clone()); }
-        let new_locale = new_locale.unwrap();
-        if !self.supports_locale(&new_locale) {
-            panic!("Unsupported locale {}", new_locale.standard_tag());
+    /// Locales whose assets are already loaded, such as preloaded ones,
+    /// are not loaded again.
+    ///
+    /// Locales are processed in a stable order: the locale first, then its
+    /// fallbacks depth first in declaration order, which is also the order
+    /// of `current_locale_seq_ordered`. Their resources are requested in
+    /// that order, although HTTP responses may complete in any order.
+    ///
+    /// If the locale is not supported or any resource fails to load, the error
+    /// is logged as a warning and the method returns `false`, otherwise `true`.
+    /// Use `try_load` to handle the error.
+    pub async fn load(&mut self, new_locale: Option<Locale>) -> bool {
+        match self.try_load(new_locale).await {
+            Ok(()) => true,
+            Err(error) => {
+                log::warn!("{}", error);
+                false
+            },
+        }
+    }
+
+    /// Attempts to load the specified locale and its fallbacks like `update_locale`,
+    /// blocking the current thread. See `load_blocking`.
+    pub fn update_locale_blocking(&mut self, new_locale: Locale) -> bool {
+        self.load_blocking(Some(new_locale))
+    }
+
+    /// Attempts to load a locale and its fallbacks like `load`, blocking
+    /// the current thread instead of requiring an asynchronous runtime,
+    /// so that programs without one can load local assets.
+    ///
+    /// Only assets loaded via `MessageLocatorLoadVia::FileSystem`, `Memory`
+    /// or `Static` are supported. Assets loaded via `Http` or `Fetch`, or through
+    /// a custom `AssetLoader`, which may rely on an asynchronous runtime such as
+    /// tokio, require `load`; for them, a warning is logged and the method returns `false`.
+    pub fn load_blocking(&mut self, new_locale: Option<Locale>) -> bool {
+        if self._assets_load_via.is_http() || self._assets_loader.is_some() {
+            log::warn!("Assets loaded via HTTP or a custom loader require the asynchronous load.");
+            return false;
         }
-        let mut to_load: HashSet<Locale> = hashset![new_locale.clone()];
-        self.enumerate_fallbacks(new_locale.clone(), &mut to_load);
+        futures::executor::block_on(self.load(new_locale))
+    }
 
-        let mut new_assets: HashMap<Locale, serde_json::Value> = hashmap![];
-        for locale in to_load {
-            let res = self.load_single_locale(&locale).await;
-            if res.is_none() {
+    /// Loads only the given base files for a locale and its fallbacks,
+    /// merging them into the assets already loaded, such as to load the
+    /// messages of a feature area on navigation. Other loaded base files
+    /// are kept regardless of `MessageLocatorAssetOptions::clean_unused`.
+    /// The shared base files are not loaded.
+    ///
+    /// Like `load`, the locale becomes the current locale and locale-change
+    /// callbacks are invoked. A later `load` of a locale whose base files
+    /// were partially loaded this way loads all of its base files.
+    ///
+    /// This is not supported with `AssetFormat::Bundle` nor with
+    /// `MessageLocatorAssetOptions::single_file`. If the locale is not
+    /// supported or any resource fails to load, the error is logged as
+    /// a warning and the method returns `false`.
+    pub async fn load_namespaces(&mut self, new_locale: Option<Locale>, names: &[String]) -> bool {
+        if self._assets_single_file || (matches!(self._assets_format, AssetFormat::Bundle) && self._assets_loader.is_none()) {
+            log::warn!("Base files cannot be loaded individually from bundles or single-file locales.");
+            return false;
+        }
+        let new_locale = self.canonical_locale(&new_locale.unwrap_or_else(|| self._default_locale.clone()));
+        if !self.supports_locale(&new_locale) {
+            log::warn!("{}", MessageLoadError::Unsupported { locale: new_locale.standard_tag().to_string() });
+            return false;
+        }
+        let locales = self.lookup_chain(&new_locale);
+        let this = &*self;
+        let loaded = try_join_all(locales.iter().map(|locale| async move {
+            let locale_path_comp = this._locale_path_components.get(locale)
+                .ok_or_else(|| MessageLoadError::Unsupported { locale: locale.standard_tag().to_string() })?;
+            let language_path_comp = this.language_path_component(locale).filter(|c| c != locale_path_comp);
+            try_join_all(names.iter().map(|base_name| {
+                this.load_locale_base_file(locale_path_comp, language_path_comp.as_deref(), base_name)
+            })).await
+        })).await;
+        let loaded = match loaded {
+            Ok(loaded) => loaded,
+            Err(error) => {
+                log::warn!("{}", error);
                 return false;
+            },
+        };
+        for (locale, roots) in locales.into_iter().zip(loaded) {
+            let mut root = self._assets.get(&locale).cloned().unwrap_or_else(|| serde_json::Value::Object(serde_json::Map::new()));
+            let mut sources = self._key_sources.as_ref().and_then(|s| s.get(&locale).cloned()).unwrap_or_default();
+            for (base_name, base_root) in names.iter().zip(roots) {
+                self.apply_deep(base_name, base_root, &mut root, &mut sources);
+            }
+            if !self._assets.contains_key(&locale) {
+                self._partial_locales.insert(locale.clone());
+            }
+            Shared::make_mut(&mut self._assets).insert(locale.clone(), root);
+            if let Some(key_sources) = self._key_sources.as_mut() {
+                Shared::make_mut(key_sources).insert(locale, sources);
             }
-            new_assets.insert(locale.clone(), res.unwrap());
         }
+        self._current_locale = Some(new_locale.clone());
+        for listener in self._locale_change_listeners.iter() {
+            listener(&new_locale);
+        }
+        true
+    }
+
+    /// Attempts to load a locale and its fallbacks like `load`,
+    /// returning the cause if any resource fails to load.
+    /// Nothing changes in the locator when an error is returned.
+    pub async fn try_load(&mut self, mut new_locale: Option<Locale>) -> Result<(), MessageLoadError> {
+        if new_locale.is_none() { new_locale = Some(self._default_locale.clone()); }
+        let new_locale = self.canonical_locale(&new_locale.unwrap());
+        if !self.supports_locale(&new_locale) {
+            return Err(MessageLoadError::Unsupported { locale: new_locale.standard_tag().to_string() });
+        }
+        // the locale and its fallbacks in lookup order
+        let to_load = self.lookup_chain(&new_locale);
+        let to_fetch: Vec<Locale> = to_load.iter().filter(|locale| !self.is_fully_loaded(locale)).cloned().collect();
+        let fetched = try_join_all(to_fetch.iter().map(|locale| self.load_single_locale(locale))).await?;
+        let mut fetched: HashMap<Locale, LocaleAssets> = to_fetch.into_iter().zip(fetched).collect();
+        let new_assets: Vec<(Locale, LocaleAssets)> = to_load.into_iter().map(|locale| {
+            let entry = fetched.remove(&locale).unwrap_or_else(|| {
+                let sources = self._key_sources.as_ref().and_then(|s| s.get(&locale).cloned()).unwrap_or_default();
+                (self._assets[&locale].clone(), sources)
+            });
+            (locale, entry)
+        }).collect();
         if self._assets_clean_unused {
-            Rc::get_mut(&mut self._assets).unwrap().clear();
+            Shared::make_mut(&mut self._assets).clear();
+            self._partial_locales.clear();
+            if let Some(key_sources) = self._key_sources.as_mut() {
+                Shared::make_mut(key_sources).clear();
+            }
         }
 
-        for (locale, root) in new_assets {
-            Rc::get_mut(&mut self._assets).unwrap().insert(locale, root);
+        for (locale, (root, sources)) in new_assets {
+            self._partial_locales.remove(&locale);
+            Shared::make_mut(&mut self._assets).insert(locale.clone(), root);
+            if let Some(key_sources) = self._key_sources.as_mut() {
+                Shared::make_mut(key_sources).insert(locale, sources);
+            }
         }
         self._current_locale = Some(new_locale.clone());
         // let new_locale_code = unic_langid::LanguageIdentifier::from_bytes(new_locale.clone().standard_tag().to_string().as_ref()).unwrap();
+        for listener in self._locale_change_listeners.iter() {
+            listener(&new_locale);
+        }
+
+        Ok(())
+    }
+
+    /// Loads the current locale and its fallbacks again, replacing their
+    /// cached assets with fresh contents, such as to pick up edited files
+    /// during development. If no locale is loaded, this does nothing and
+    /// returns `true`. Locale-change callbacks are not invoked.
+    ///
+    /// If any resource fails to load, the error is logged as a warning,
+    /// the cached assets are kept and the method returns `false`.
+    pub async fn reload(&mut self) -> bool {
+        let locales: Vec<Locale> = self.current_locale_seq().into_iter().collect();
+        match try_join_all(locales.iter().map(|locale| self.load_single_locale(locale))).await {
+            Ok(loaded) => {
+                for (locale, (root, sources)) in locales.into_iter().zip(loaded) {
+                    self._partial_locales.remove(&locale);
+                    Shared::make_mut(&mut self._assets).insert(locale.clone(), root);
+                    if let Some(key_sources) = self._key_sources.as_mut() {
+                        Shared::make_mut(key_sources).insert(locale, sources);
+                    }
+                }
+                true
+            },
+            Err(error) => {
+                log::warn!("{}", error);
+                false
+            },
+        }
+    }
+
+    /// Returns the interval of automatic reloads, if any.
+    /// See `MessageLocatorAssetOptions::reload_interval`.
+    pub fn reload_interval(&self) -> Option<std::time::Duration> {
+        self._reload_interval
+    }
+
+    /// Registers a callback invoked with the new locale whenever `load` or
+    /// `update_locale` succeeds, such as to re-render a user interface.
+    /// Multiple callbacks may be registered and are invoked in registration order.
+    /// Callbacks are not invoked when loading fails, nor by `preload`.
+    /// Clones of the locator keep the callbacks registered before cloning.
+    #[cfg(not(feature = "sync"))]
+    pub fn on_locale_change(&mut self, callback: Box<dyn Fn(&Locale)>) {
+        self._locale_change_listeners.push(Shared::from(callback));
+    }
+
+    /// Registers a callback invoked with the new locale whenever `load` or
+    /// `update_locale` succeeds, such as to re-render a user interface.
+    /// Multiple callbacks may be registered and are invoked in registration order.
+    /// Callbacks are not invoked when loading fails, nor by `preload`.
+    /// Clones of the locator keep the callbacks registered before cloning.
+    ///
+    /// With the `sync` feature, the callback must be `Send + Sync`.
+    #[cfg(feature = "sync")]
+    pub fn on_locale_change(&mut self, callback: Box<dyn Fn(&Locale) + Send + Sync>) {
+        self._locale_change_listeners.push(Shared::from(callback));
+    }
+
+    /// Loads the specified locales and their fallbacks into the cache
+    /// without changing the current locale, so that switching to them later
+    /// through `update_locale` or `load` does not load them again.
+    /// Locales that are already loaded are skipped.
+    ///
+    /// If a locale is not supported or any resource fails to load,
+    /// the cause is returned and nothing is added to the cache.
+    pub async fn preload(&mut self, locales: &[Locale]) -> Result<(), MessageLoadError> {
+        let mut to_load = Vec::<Locale>::new();
+        for locale in locales.iter() {
+            if !self.supports_locale(locale) {
+                return Err(MessageLoadError::Unsupported { locale: locale.standard_tag().to_string() });
+            }
+            self.enumerate_chain(self.canonical_locale(locale), &mut to_load);
+        }
+        self.load_into_cache(to_load).await
+    }
+
+    /// Loads every supported locale into the cache, so that switching
+    /// locales later never loads resources again. Locales that are already
+    /// loaded are skipped, and `MessageLocatorAssetOptions::clean_unused`
+    /// is ignored during the preload; enable it only if a later `load`
+    /// should discard the locales it does not need.
+    ///
+    /// The current locale is not changed; it stays unset until an explicit
+    /// `load` or `update_locale`.
+    ///
+    /// If any resource fails to load, the error is logged as a warning and
+    /// the method returns `false`, otherwise `true`. Use `try_load_all` to handle the error.
+    pub async fn load_all(&mut self) -> bool {
+        match self.try_load_all().await {
+            Ok(()) => true,
+            Err(error) => {
+                log::warn!("{}", error);
+                false
+            },
+        }
+    }
+
+    /// Loads every supported locale into the cache like `load_all`,
+    /// returning the cause if any resource fails to load.
+    /// Nothing is added to the cache when an error is returned.
+    pub async fn try_load_all(&mut self) -> Result<(), MessageLoadError> {
+        let mut to_load: Vec<Locale> = self.supported_locales().into_iter().collect();
+        to_load.sort_by_key(|locale| locale.standard_tag().to_string());
+        self.load_into_cache(to_load).await
+    }
+
+    /// Loads locales into the cache in the given order,
+    /// skipping those that are fully loaded.
+    async fn load_into_cache(&mut self, to_load: Vec<Locale>) -> Result<(), MessageLoadError> {
+        let to_load: Vec<Locale> = to_load.into_iter().filter(|locale| !self.is_fully_loaded(locale)).collect();
+        let loaded = try_join_all(to_load.iter().map(|locale| self.load_single_locale(locale))).await?;
+        for (locale, (root, sources)) in to_load.into_iter().zip(loaded) {
+            self._partial_locales.remove(&locale);
+            Shared::make_mut(&mut self._assets).insert(locale.clone(), root);
+            if let Some(key_sources) = self._key_sources.as_mut() {
+                Shared::make_mut(key_sources).insert(locale, sources);
+            }
+        }
+        Ok(())
+    }
+
+    async fn load_single_locale(&self, locale: &Locale) -> Result<LocaleAssets, MessageLoadError> {
+        let mut r = serde_json::Value::Object(serde_json::Map::new());
+        let mut sources = HashMap::<String, String>::new();
+        let locale_path_comp = self._locale_path_components.get(locale)
+            .ok_or_else(|| MessageLoadError::Unsupported { locale: locale.standard_tag().to_string() })?;
+        if let (AssetFormat::Bundle, None) = (self._assets_format, self._assets_loader.as_ref()) {
+            let res_path = format!("{}/{}.{}", self._assets_src, locale_path_comp, self.asset_extension());
+            let content = self.load_bytes(&res_path).await?;
+            let messages: Vec<(String, String)> = bincode::deserialize(&content)
+                .map_err(|source| MessageLoadError::Parse { path: res_path.clone(), source })?;
+            for (key, message) in messages {
+                let id = split_id(&key, self._id_separator);
+                set_leaf(&mut r, &id.iter().map(|s| s.as_str()).collect::<Vec<&str>>(), serde_json::Value::String(message));
+            }
+            return Ok((r, sources));
+        }
+        let language_path_comp = &self.language_path_component(locale).filter(|c| c != locale_path_comp);
+        if self._assets_single_file {
+            let mut root = match language_path_comp.as_ref() {
+                Some(language_path_comp) => {
+                    let (mut language_root, root) = try_join(
+                        self.load_locale_file(language_path_comp),
+                        self.load_locale_file(locale_path_comp),
+                    ).await?;
+                    merge_deep(&mut language_root, root);
+                    language_root
+                },
+                None => self.load_locale_file(locale_path_comp).await?,
+            };
+            if self._case_insensitive_keys {
+                root = MessageLocator::lowercase_keys(root);
+            }
+            self.warn_undeclared_variables(&root, locale_path_comp);
+            return Ok((root, sources));
+        }
+        // every file is requested at once; the results are applied in order
+        let shared_roots = try_join_all(self._assets_shared_base_file_names.iter().map(|base_name| self.load_base_file(None, base_name)));
+        let roots = try_join_all(self._assets_base_file_names.iter().map(|base_name| {
+            self.load_locale_base_file(locale_path_comp, language_path_comp.as_deref(), base_name)
+        }));
+        let (shared_roots, roots) = try_join(shared_roots, roots).await?;
+        for (base_name, root) in self._assets_shared_base_file_names.iter().zip(shared_roots) {
+            self.apply_deep(base_name, root, &mut r, &mut sources);
+        }
+        for (base_name, root) in self._assets_base_file_names.iter().zip(roots) {
+            self.apply_deep(base_name, root, &mut r, &mut sources);
+        }
+        self.warn_undeclared_variables(&r, locale_path_comp);
+        Ok((r, sources))
+    }
+
+    /// Logs a warning for each undeclared variable of the messages loaded
+    /// for a locale directory. See `validate_variables`.
+    fn warn_undeclared_variables(&self, root: &serde_json::Value, locale_path_comp: &str) {
+        let mut undeclared = Vec::<(String, String)>::new();
+        self.collect_undeclared_variables(root, String::new(), &mut undeclared);
+        for (id, name) in undeclared {
+            log::warn!("Message {} in {} uses undeclared variable {}.", id, locale_path_comp, name);
+        }
+    }
+
+    /// Loads a base file of a locale directory, merged over the same base file
+    /// of its language directory if the locale inherits language files.
+    async fn load_locale_base_file(&self, locale_path_comp: &str, language_path_comp: Option<&str>, base_name: &str) -> Result<serde_json::Value, MessageLoadError> {
+        match language_path_comp {
+            Some(language_path_comp) => {
+                let (mut language_root, root) = try_join(
+                    self.load_base_file(Some(language_path_comp), base_name),
+                    self.load_base_file(Some(locale_path_comp), base_name),
+                ).await?;
+                merge_deep(&mut language_root, root);
+                Ok(language_root)
+            },
+            None => self.load_base_file(Some(locale_path_comp), base_name).await,
+        }
+    }
+
+    /// Returns the directory of a regional locale's language whose files
+    /// it inherits, if `MessageLocatorAssetOptions::inherit_language_files` is enabled.
+    fn language_path_component(&self, locale: &Locale) -> Option<String> {
+        if !self._assets_inherit_language_files || locale.standard_tag().get_region().is_none() {
+            return None;
+        }
+        let language = locale.language();
+        let language_locale = parse_locale(&language).ok();
+        Some(language_locale.and_then(|l| self._locale_path_components.get(&l).cloned()).unwrap_or(language))
+    }
+
+    /// Returns the file name of a base file in a locale directory,
+    /// as given to `MessageLocatorAssetOptions::file_name_for`.
+    fn file_name<'a>(&'a self, path_comp: &str, base_name: &'a str) -> &'a str {
+        self._assets_file_names.get(&(path_comp.to_string(), base_name.to_string())).map(|s| s.as_str()).unwrap_or(base_name)
+    }
+
+    /// Loads a base file of a locale directory, or a shared base file
+    /// if `path_comp` is `None`.
+    async fn load_base_file(&self, path_comp: Option<&str>, base_name: &str) -> Result<serde_json::Value, MessageLoadError> {
+        let base_name = match path_comp {
+            Some(path_comp) => self.file_name(path_comp, base_name),
+            None => base_name,
+        };
+        if let Some(loader) = self._assets_loader.as_ref() {
+            let root = loader.load(path_comp.unwrap_or(""), base_name).await?;
+            log::debug!("Loaded resource {}/{} through the asset loader.", path_comp.unwrap_or(""), base_name);
+            return Ok(root);
+        }
+        if let MessageLocatorLoadVia::Memory = self._assets_load_via {
+            let key = match path_comp {
+                Some(path_comp) => format!("{}/{}", path_comp, base_name),
+                None => base_name.to_string(),
+            };
+            let root = self._assets_memory.get(&key).cloned().ok_or_else(|| MessageLoadError::Io {
+                path: key.clone(),
+                source: std::io::Error::new(std::io::ErrorKind::NotFound, "no in-memory asset"),
+            })?;
+            log::debug!("Loaded in-memory resource {}.", key);
+            return Ok(root);
+        }
+        if let MessageLocatorLoadVia::Static = self._assets_load_via {
+            let key = (path_comp.unwrap_or("").to_string(), base_name.to_string());
+            let res_path = format!("{}/{}", key.0, key.1);
+            let content = self._assets_static.get(&key).ok_or_else(|| MessageLoadError::Io {
+                path: res_path.clone(),
+                source: std::io::Error::new(std::io::ErrorKind::NotFound, "no static asset"),
+            })?;
+            log::debug!("Loaded static resource {}.", res_path);
+            return self.parse_resource(&res_path, content.as_bytes());
+        }
+        let res_path = match path_comp {
+            Some(path_comp) => format!("{}/{}/{}.{}", self._assets_src, path_comp, base_name, self.asset_extension()),
+            None => format!("{}/{}.{}", self._assets_shared_src, base_name, self.asset_extension()),
+        };
+        self.load_resource(&res_path).await
+    }
+
+    /// Loads the file holding every message of a locale directory,
+    /// if `MessageLocatorAssetOptions::single_file` is enabled.
+    async fn load_locale_file(&self, path_comp: &str) -> Result<serde_json::Value, MessageLoadError> {
+        if let Some(loader) = self._assets_loader.as_ref() {
+            return loader.load(path_comp, "").await;
+        }
+        match self._assets_load_via {
+            MessageLocatorLoadVia::Memory => self._assets_memory.get(path_comp).cloned().ok_or_else(|| MessageLoadError::Io {
+                path: path_comp.to_string(),
+                source: std::io::Error::new(std::io::ErrorKind::NotFound, "no in-memory asset"),
+            }),
+            MessageLocatorLoadVia::Static => {
+                let content = self._assets_static.get(&(path_comp.to_string(), String::new())).ok_or_else(|| MessageLoadError::Io {
+                    path: path_comp.to_string(),
+                    source: std::io::Error::new(std::io::ErrorKind::NotFound, "no static asset"),
+                })?;
+                self.parse_resource(path_comp, content.as_bytes())
+            },
+            _ => self.load_resource(&format!("{}/{}.{}", self._assets_src, path_comp, self.asset_extension())).await,
+        }
+    }
+
+    async fn load_resource(&self, res_path: &str) -> Result<serde_json::Value, MessageLoadError> {
+        let content = self.load_bytes(res_path).await?;
+        self.parse_resource(res_path, &content)
+    }
+
+    fn parse_resource(&self, res_path: &str, content: &[u8]) -> Result<serde_json::Value, MessageLoadError> {
+        let parse_error = |source: Box<dyn std::error::Error + Send + Sync>| MessageLoadError::Parse { path: res_path.to_string(), source };
+        let (metadata, body) = if self._assets_front_matter { split_front_matter(content) } else { (None, content) };
+        let root = self._assets_format.parse(body).map_err(parse_error)?;
+        let mut root = match self._assets_response_transform.as_ref() {
+            Some(transform) if self._assets_load_via.is_http() => transform(root),
+            _ => root,
+        };
+        if let Some(metadata) = metadata {
+            let metadata: serde_json::Value = serde_yaml::from_str(metadata).map_err(|source| parse_error(Box::new(source)))?;
+            if let Some(root) = root.as_object_mut() {
+                root.insert(String::from("_meta"), metadata);
+            }
+        }
+        Ok(root)
+    }
+
+    async fn load_bytes(&self, res_path: &str) -> Result<Vec<u8>, MessageLoadError> {
+        let content = self.fetch_bytes(res_path).await?;
+        log::debug!("Loaded resource at {}.", res_path);
+        self._assets_compression.decompress(content).map_err(|source| MessageLoadError::Parse { path: res_path.to_string(), source: Box::new(source) })
+    }
+
+    /// Returns the file extension of assets, such as `json.gz`.
+    fn asset_extension(&self) -> String {
+        match self._assets_compression {
+            Compression::None => self._assets_format.extension().to_string(),
+            Compression::Gzip => format!("{}.gz", self._assets_format.extension()),
+        }
+    }
+
+    async fn fetch_bytes(&self, res_path: &str) -> Result<Vec<u8>, MessageLoadError> {
+        match self._assets_load_via {
+            MessageLocatorLoadVia::FileSystem => {
+                std::fs::read(res_path).map_err(|source| MessageLoadError::Io { path: res_path.to_string(), source })
+            },
+            MessageLocatorLoadVia::Http => {
+                let cache_path = self.http_cache_path(res_path);
+                match self.fetch_http(res_path).await {
+                    Ok(content) => {
+                        if let Some(cache_path) = cache_path.as_ref() {
+                            let written = cache_path.parent().map_or(Ok(()), std::fs::create_dir_all).and_then(|_| std::fs::write(cache_path, &content));
+                            if let Err(error) = written {
+                                log::warn!("Failed to cache resource at {}: {}", res_path, error);
+                            }
+                        }
+                        Ok(content)
+                    },
+                    Err(source) => match cache_path.and_then(|cache_path| std::fs::read(cache_path).ok()) {
+                        Some(content) => {
+                            log::warn!("Failed to load resource at {}, using the cached copy: {}", res_path, source);
+                            Ok(content)
+                        },
+                        None => Err(MessageLoadError::Http { path: res_path.to_string(), source }),
+                    },
+                }
+            },
+            #[cfg(feature = "wasm")]
+            MessageLocatorLoadVia::Fetch => {
+                fetch_in_browser(res_path).await.map_err(|message| MessageLoadError::Fetch { path: res_path.to_string(), message })
+            },
+            #[cfg(not(feature = "wasm"))]
+            MessageLocatorLoadVia::Fetch => Err(MessageLoadError::Io {
+                path: res_path.to_string(),
+                source: std::io::Error::new(std::io::ErrorKind::Unsupported, "the fetch API requires the wasm feature"),
+            }),
+            MessageLocatorLoadVia::Memory | MessageLocatorLoadVia::Static => Err(MessageLoadError::Io {
+                path: res_path.to_string(),
+                source: std::io::Error::new(std::io::ErrorKind::Unsupported, "bundles cannot be loaded from memory or static assets"),
+            }),
+        }
+    }
+
+    /// Requests a resource over HTTP, retrying as configured by
+    /// `MessageLocatorAssetOptions::http_retries`.
+    async fn fetch_http(&self, res_path: &str) -> Result<Vec<u8>, reqwest::Error> {
+        let mut attempt = 0;
+        loop {
+            match self.request_http(res_path).await {
+                Err(source) if attempt < self._http_retries && source.status().filter(|s| !s.is_server_error()).is_none() => {
+                    let delay = self._http_retry_backoff.checked_mul(2u32.saturating_pow(attempt)).unwrap_or(std::time::Duration::MAX);
+                    log::debug!("Retrying resource at {} in {:?}: {}", res_path, delay, source);
+                    #[cfg(not(target_arch = "wasm32"))]
+                    tokio::time::sleep(delay).await;
+                    #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+                    gloo_timers::future::sleep(delay).await;
+                    attempt += 1;
+                },
+                r => return r,
+            }
+        }
+    }
+
+    async fn request_http(&self, res_path: &str) -> Result<Vec<u8>, reqwest::Error> {
+        let response = self._http_client.get(res_path).send().await?.error_for_status()?;
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    /// Returns the file caching an HTTP resource, if
+    /// `MessageLocatorAssetOptions::http_cache_dir` is set. Files are cached
+    /// by their path under the assets source, or under `_shared` for
+    /// shared base files, so that the cache survives a change of host.
+    fn http_cache_path(&self, res_path: &str) -> Option<std::path::PathBuf> {
+        let cache_dir = self._http_cache_dir.as_ref()?;
+        if let Some(rest) = res_path.strip_prefix(&format!("{}/", self._assets_shared_src)) {
+            return Some(cache_dir.join("_shared").join(rest));
+        }
+        res_path.strip_prefix(&format!("{}/", self._assets_src)).map(|rest| cache_dir.join(rest))
+    }
+
+    /// Places the contents of a base file at its namespace in `output`
+    /// according to the merge strategy, recording the sources of its messages.
+    fn apply_deep(&self, base_name: &String, mut assign: serde_json::Value, mut output: &mut serde_json::Value, sources: &mut HashMap<String, String>) {
+        let name = if self._case_insensitive_keys { base_name.to_lowercase() } else { base_name.clone() };
+        if self._case_insensitive_keys {
+            assign = MessageLocator::lowercase_keys(assign);
+        }
+        let mut names: Vec<&str> = name.split("/").collect();
+        let last_name = names.pop().unwrap();
+        for name in names.iter() {
+            let r = output.get(*name);
+            if r.is_none() || r.unwrap().as_object().is_none() {
+                let r = serde_json::Value::Object(serde_json::Map::new());
+                output.as_object_mut().unwrap().insert(String::from(*name), r);
+            }
+            output = output.get_mut(*name).unwrap();
+        }
+        names.push(last_name);
+        match (self._assets_merge_strategy, output.get_mut(last_name)) {
+            (MergeStrategy::KeepExisting, Some(_)) => {},
+            (MergeStrategy::DeepMerge, Some(existing)) => {
+                self.record_key_sources(base_name, &names, &assign, false, sources);
+                merge_deep(existing, assign);
+            },
+            _ => {
+                self.record_key_sources(base_name, &names, &assign, true, sources);
+                output.as_object_mut().unwrap().insert(String::from(last_name), assign);
+            },
+        }
+    }
+
+    /// Records `base_name` as the source of every message in `value`,
+    /// which is placed at the namespace `names`. If `replace` is `true`,
+    /// previous records for that namespace are removed first.
+    fn record_key_sources(&self, base_name: &str, names: &[&str], value: &serde_json::Value, replace: bool, sources: &mut HashMap<String, String>) {
+        if self._key_sources.is_none() {
+            return;
+        }
+        let prefix = names.join(&self._id_separator.to_string());
+        if replace {
+            sources.retain(|k, _| k != &prefix && !k.starts_with(&format!("{}{}", prefix, self._id_separator)));
+        }
+        self.for_each_message(value, prefix, &mut |key, _| {
+            sources.insert(key, base_name.to_string());
+        });
+    }
+
+    /// Calls `f` with the full identifier and the value of every
+    /// string leaf under `value`.
+    fn for_each_message(&self, value: &serde_json::Value, prefix: String, f: &mut dyn FnMut(String, &str)) {
+        match value {
+            serde_json::Value::Object(map) => {
+                for (k, v) in map.iter() {
+                    let key = join_id(&prefix, k, self._id_separator);
+                    self.for_each_message(v, key, f);
+                }
+            },
+            serde_json::Value::String(message) => f(prefix, message),
+            _ => {},
+        }
+    }
+
+    fn lowercase_keys(value: serde_json::Value) -> serde_json::Value {
+        match value {
+            serde_json::Value::Object(map) => serde_json::Value::Object(
+                map.into_iter().map(|(k, v)| (k.to_lowercase(), MessageLocator::lowercase_keys(v))).collect()
+            ),
+            value => value,
+        }
+    }
+
+    /// Returns `true` if all assets of a locale are loaded, that is,
+    /// not only the base files given to `load_namespaces`.
+    fn is_fully_loaded(&self, locale: &Locale) -> bool {
+        self._assets.contains_key(locale) && !self._partial_locales.contains(locale)
+    }
+
+    /// Returns the direct fallbacks of a locale: the configured ones, followed
+    /// by its language locale if `MessageLocatorOptions::implicit_language_fallback`
+    /// is enabled.
+    fn locale_fallbacks(&self, locale: &Locale) -> Vec<Locale> {
+        let mut r = self._fallbacks.get(locale).cloned().unwrap_or_default();
+        if self._implicit_language_fallback && locale.standard_tag().get_region().is_some() {
+            if let Ok(language) = parse_locale(locale.language()).map(|language| self.canonical_locale(&language)) {
+                if &language != locale && self._supported_locales.contains(&language) && !r.contains(&language) {
+                    r.push(language);
+                }
+            }
+        }
+        r
+    }
+
+    /// Returns a locale followed by its fallbacks, depth first,
+    /// in the order messages are looked up. Each locale appears once.
+    fn lookup_chain(&self, locale: &Locale) -> Vec<Locale> {
+        let mut r = Vec::<Locale>::new();
+        self.enumerate_chain(locale.clone(), &mut r);
+        r
+    }
+
+    fn enumerate_chain(&self, locale: Locale, output: &mut Vec<Locale>) {
+        if output.contains(&locale) {
+            return;
+        }
+        output.push(locale.clone());
+        for fl in self.locale_fallbacks(&locale) {
+            self.enumerate_chain(fl, output);
+        }
+    }
+
+    /// Returns the locales in which a message would be looked up if `requested`
+    /// were loaded, in lookup order. This is a debugging aid.
+    ///
+    /// The plan starts with the negotiated locale: `requested` itself if
+    /// supported, otherwise a supported locale of the same language, preferring
+    /// the language-only locale, or otherwise the default locale. Each locale
+    /// is followed by its language locale if it inherits that language's files
+    /// (see `MessageLocatorAssetOptions::inherit_language_files`), and then by
+    /// its fallbacks, depth first. The default locale appears
+    /// only when it is one of the fallbacks.
+    pub fn resolution_plan(&self, requested: &Locale) -> Vec<Locale> {
+        let mut r = Vec::<Locale>::new();
+        self.enumerate_plan(self.negotiate(requested), &mut r);
+        r
+    }
+
+    fn enumerate_plan(&self, locale: Locale, output: &mut Vec<Locale>) {
+        if output.contains(&locale) {
+            return;
+        }
+        output.push(locale.clone());
+        if self.language_path_component(&locale).is_some() {
+            if let Ok(language) = parse_locale(locale.language()) {
+                if !output.contains(&language) {
+                    output.push(language);
+                }
+            }
+        }
+        for fl in self.locale_fallbacks(&locale) {
+            self.enumerate_plan(fl, output);
+        }
+    }
+
+    /// Selects the supported locale that best matches `requested`.
+    fn negotiate(&self, requested: &Locale) -> Locale {
+        if self.supports_locale(requested) {
+            return requested.clone();
+        }
+        let language = requested.language();
+        let mut candidates: Vec<&Locale> = self._supported_locales.iter()
+            .filter(|locale| locale.language() == language)
+            .collect();
+        candidates.sort_by_key(|locale| (locale.standard_tag().get_region().is_some(), locale.standard_tag().to_string()));
+        candidates.first().map(|locale| (*locale).clone()).unwrap_or_else(|| self._default_locale.clone())
+    }
+
+    /// Traces how a message identifier resolves from the current locale,
+    /// recording each locale searched, whether it defines the message and
+    /// which base file provided it, along with the unformatted message chosen.
+    /// Base files are only known if `MessageLocatorOptions::track_key_sources`
+    /// is enabled. The trace prints in a human-readable form.
+    ///
+    /// This is a debugging tool for messages that resolve unexpectedly.
+    pub fn explain<S: ToString>(&self, id: S) -> ResolutionTrace {
+        let id = id.to_string();
+        let lookup_id = if self._case_insensitive_keys { id.to_lowercase() } else { id.clone() };
+        let split = split_id(&lookup_id, self._id_separator);
+        let mut r = ResolutionTrace { id, steps: vec![], message: None };
+        for locale in self._current_locale.iter().flat_map(|locale| self.lookup_chain(locale)) {
+            let message = self.resolve_id(self._assets.get(&locale), &split);
+            let source = message.as_ref().and_then(|_| self.key_source(&locale, &lookup_id));
+            r.steps.push(ResolutionStep { locale, found: message.is_some(), source });
+            if message.is_some() {
+                r.message = message;
+                break;
+            }
+        }
+        r
+    }
+
+    /// Returns the base file name that provided the message at `id`
+    /// in the given locale. Fallback locales are not consulted.
+    ///
+    /// This always returns `None` unless `MessageLocatorOptions::track_key_sources`
+    /// was enabled.
+    pub fn key_source<S: ToString>(&self, locale: &Locale, id: S) -> Option<String> {
+        let id = if self._case_insensitive_keys { id.to_string().to_lowercase() } else { id.to_string() };
+        self._key_sources.as_ref()?.get(locale)?.get(&id).cloned()
+    }
+
+    /// Compiles the messages loaded for a locale into a compact binary bundle.
+    /// Saving it as `{src}/{locale}.bundle` allows loading it with
+    /// `AssetFormat::Bundle`, which skips JSON parsing at startup.
+    ///
+    /// The bundle holds the flattened string messages of the locale,
+    /// including those from shared base files.
+    pub fn compile_bundle(&self, locale: &Locale) -> Vec<u8> {
+        let mut messages = Vec::<(String, String)>::new();
+        if let Some(root) = self._assets.get(locale) {
+            self.for_each_message(root, String::new(), &mut |key, message| {
+                messages.push((key, message.to_string()));
+            });
+        }
+        messages.sort();
+        bincode::serialize(&messages).unwrap()
+    }
+
+    /// Checks the assets of every supported locale without loading them
+    /// into the locator, collecting every issue found: assets that are missing,
+    /// not valid UTF-8, empty or not an object. Shared base files are
+    /// checked once and reported with no locale. Inherited language files
+    /// (see `MessageLocatorAssetOptions::inherit_language_files`) are checked
+    /// once, reported with the first locale that uses them.
+    ///
+    /// Assets loaded via `MessageLocatorLoadVia::FileSystem`, `Memory` and `Static`
+    /// are checked, as well as the single files of
+    /// `MessageLocatorAssetOptions::single_file`. In-memory and static assets
+    /// are reported by their key, such as `en/_`. Assets loaded over HTTP or
+    /// through an `AssetLoader` cannot be checked without loading them;
+    /// for those this returns a single `PreflightProblem::Unsupported` issue.
+    pub fn preflight(&self) -> Vec<PreflightIssue> {
+        let mut r = Vec::<PreflightIssue>::new();
+        let bundle = matches!(self._assets_format, AssetFormat::Bundle);
+        let supported = match self._assets_load_via {
+            MessageLocatorLoadVia::FileSystem => true,
+            MessageLocatorLoadVia::Memory | MessageLocatorLoadVia::Static => !bundle,
+            _ => false,
+        };
+        if !supported || self._assets_loader.is_some() {
+            r.push(PreflightIssue { path: self._assets_src.clone(), locale: None, problem: PreflightProblem::Unsupported });
+            return r;
+        }
+        let mut locales: Vec<&Locale> = self._supported_locales.iter().collect();
+        locales.sort_by_key(|locale| locale.standard_tag().to_string());
+        if bundle {
+            for locale in locales {
+                let path = format!("{}/{}.{}", self._assets_src, self._locale_path_components.get(locale).unwrap(), self.asset_extension());
+                if let Err(problem) = self.preflight_bundle(&path) {
+                    r.push(PreflightIssue { path, locale: Some(locale.clone()), problem });
+                }
+            }
+            return r;
+        }
+        // single files hold every message, so base file names are not used
+        let base_names: Vec<Option<&str>> = if self._assets_single_file {
+            vec![None]
+        } else {
+            self._assets_base_file_names.iter().map(|base_name| Some(base_name.as_str())).collect()
+        };
+        if !self._assets_single_file {
+            for base_name in self._assets_shared_base_file_names.iter() {
+                let path = self.preflight_path(None, Some(base_name));
+                if let Err(problem) = self.preflight_asset(None, Some(base_name), &path) {
+                    r.push(PreflightIssue { path, locale: None, problem });
+                }
+            }
+        }
+        let mut checked = HashSet::<String>::new();
+        for locale in locales {
+            let mut path_comps = vec![self._locale_path_components.get(locale).unwrap().clone()];
+            path_comps.extend(self.language_path_component(locale));
+            for path_comp in path_comps.iter() {
+                for base_name in base_names.iter() {
+                    let path = self.preflight_path(Some(path_comp), *base_name);
+                    if !checked.insert(path.clone()) {
+                        continue;
+                    }
+                    if let Err(problem) = self.preflight_asset(Some(path_comp), *base_name, &path) {
+                        r.push(PreflightIssue { path, locale: Some(locale.clone()), problem });
+                    }
+                }
+            }
+        }
+        r
+    }
+
+    /// Returns the path of an asset as `preflight` reports it: the path of its file,
+    /// or the key of an in-memory or static asset. `path_comp` is `None` for
+    /// shared base files, and `base_name` is `None` for single files.
+    fn preflight_path(&self, path_comp: Option<&str>, base_name: Option<&str>) -> String {
+        let file_name = match (path_comp, base_name) {
+            (Some(path_comp), Some(base_name)) => Some(self.file_name(path_comp, base_name)),
+            (None, base_name) => base_name,
+            (_, None) => None,
+        };
+        match (self._assets_load_via, path_comp, file_name) {
+            (MessageLocatorLoadVia::Memory, _, _) | (MessageLocatorLoadVia::Static, _, _) => {
+                path_comp.into_iter().chain(file_name).collect::<Vec<&str>>().join("/")
+            },
+            (_, Some(path_comp), Some(file_name)) => format!("{}/{}/{}.{}", self._assets_src, path_comp, file_name, self.asset_extension()),
+            (_, Some(path_comp), None) => format!("{}/{}.{}", self._assets_src, path_comp, self.asset_extension()),
+            (_, None, file_name) => format!("{}/{}.{}", self._assets_shared_src, file_name.unwrap_or_default(), self.asset_extension()),
+        }
+    }
+
+    /// Checks an asset found at `path`, as returned by `preflight_path`.
+    fn preflight_asset(&self, path_comp: Option<&str>, base_name: Option<&str>, path: &str) -> Result<(), PreflightProblem> {
+        match self._assets_load_via {
+            MessageLocatorLoadVia::Memory => match self._assets_memory.get(path) {
+                Some(serde_json::Value::Object(_)) => Ok(()),
+                Some(_) => Err(PreflightProblem::NotAnObject),
+                None => Err(PreflightProblem::Missing),
+            },
+            MessageLocatorLoadVia::Static => {
+                let file_name = match (path_comp, base_name) {
+                    (Some(path_comp), Some(base_name)) => self.file_name(path_comp, base_name),
+                    (_, base_name) => base_name.unwrap_or_default(),
+                };
+                let key = (path_comp.unwrap_or_default().to_string(), file_name.to_string());
+                let content = self._assets_static.get(&key).ok_or(PreflightProblem::Missing)?;
+                self.preflight_content(content.as_bytes().to_vec())
+            },
+            _ => self.preflight_document(path),
+        }
+    }
+
+    fn preflight_bytes(path: &str) -> Result<Vec<u8>, PreflightProblem> {
+        let content = std::fs::read(path).map_err(|_| PreflightProblem::Missing)?;
+        if content.is_empty() {
+            return Err(PreflightProblem::Empty);
+        }
+        Ok(content)
+    }
+
+    fn preflight_document(&self, path: &str) -> Result<(), PreflightProblem> {
+        let content = self._assets_compression.decompress(MessageLocator::preflight_bytes(path)?)
+            .map_err(|error| PreflightProblem::Malformed(error.to_string()))?;
+        self.preflight_content(content)
+    }
+
+    fn preflight_content(&self, content: Vec<u8>) -> Result<(), PreflightProblem> {
+        let content = String::from_utf8(content).map_err(|_| PreflightProblem::InvalidUtf8)?;
+        if content.trim().is_empty() {
+            return Err(PreflightProblem::Empty);
+        }
+        match self._assets_format.parse(content.as_bytes()) {
+            Ok(serde_json::Value::Object(_)) => Ok(()),
+            Ok(_) => Err(PreflightProblem::NotAnObject),
+            Err(error) => Err(PreflightProblem::Malformed(error.to_string())),
+        }
+    }
+
+    fn preflight_bundle(&self, path: &str) -> Result<(), PreflightProblem> {
+        let content = self._assets_compression.decompress(MessageLocator::preflight_bytes(path)?)
+            .map_err(|error| PreflightProblem::Malformed(error.to_string()))?;
+        bincode::deserialize::<Vec<(String, String)>>(&content).map_err(|error| PreflightProblem::Malformed(error.to_string()))?;
+        Ok(())
+    }
+
+    /// Returns every supported locale whose assets are loaded, paired with
+    /// its completeness ratio against `base`: the fraction of `base`'s messages
+    /// that the locale also defines. The result is sorted by descending ratio.
+    ///
+    /// Only loaded locales are considered, so the locales of interest must
+    /// have been loaded or preloaded beforehand.
+    pub fn locales_by_completeness(&self, base: &Locale) -> Vec<(Locale, f64)> {
+        let base_keys = self.loaded_message_keys(base);
+        let mut r: Vec<(Locale, f64)> = self._supported_locales.iter()
+            .filter(|locale| self._assets.contains_key(locale))
+            .map(|locale| {
+                if base_keys.is_empty() {
+                    return (locale.clone(), 1.0);
+                }
+                let keys = self.loaded_message_keys(locale);
+                let present = base_keys.iter().filter(|k| keys.contains(*k)).count();
+                (locale.clone(), present as f64 / base_keys.len() as f64)
+            })
+            .collect();
+        r.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap().then_with(|| a.0.standard_tag().to_string().cmp(&b.0.standard_tag().to_string())));
+        r
+    }
+
+    /// Returns the full identifiers of every message loaded for a locale,
+    /// such as `inbox.title` and `inbox.messages.one`, sorted. Only string
+    /// values count as messages; fallback locales are not included.
+    pub fn message_keys(&self, locale: &Locale) -> Vec<String> {
+        let mut r: Vec<String> = self.loaded_message_keys(locale).into_iter().collect();
+        r.sort();
+        r
+    }
+
+    /// Returns, for every loaded locale other than the default locale,
+    /// the sorted identifiers of messages that the default locale defines
+    /// but that locale lacks. A key whose value is not a string counts as
+    /// missing. Locales missing no keys are included with an empty list.
+    ///
+    /// Only loaded locales are considered, so the locales of interest must
+    /// have been loaded or preloaded beforehand.
+    pub fn coverage_report(&self) -> HashMap<Locale, Vec<String>> {
+        let default_keys = self.message_keys(&self._default_locale);
+        self._assets.keys()
+            .filter(|locale| **locale != self._default_locale)
+            .map(|locale| {
+                let keys = self.loaded_message_keys(locale);
+                let missing = default_keys.iter().filter(|k| !keys.contains(*k)).cloned().collect();
+                (locale.clone(), missing)
+            })
+            .collect()
+    }
+
+    fn loaded_message_keys(&self, locale: &Locale) -> HashSet<String> {
+        let mut r = HashSet::<String>::new();
+        if let Some(root) = self._assets.get(locale) {
+            self.for_each_message(root, String::new(), &mut |key, _| {
+                r.insert(key);
+            });
+        }
+        r
+    }
+
+    /// Checks the ICU-style syntax of every message loaded for a locale,
+    /// reporting unbalanced braces and unknown keywords in blocks such as
+    /// `{count, plural, one {...} other {...}}`. Returns pairs of
+    /// message identifier and error description, sorted by identifier.
+    pub fn validate_messages(&self, locale: &Locale) -> Vec<(String, String)> {
+        let mut r = Vec::<(String, String)>::new();
+        if let Some(root) = self._assets.get(locale) {
+            self.for_each_message(root, String::new(), &mut |key, message| {
+                if let Err(error) = message_syntax::validate_icu_syntax(message) {
+                    r.push((key, error));
+                }
+            });
+        }
+        r.sort();
+        r
+    }
+
+    /// Checks the placeholders of every message loaded for a locale against
+    /// the variables declared for it. Variables are declared by a sibling key
+    /// named after the message with an `@vars` suffix, holding an array of
+    /// variable names:
+    ///
+    /// ```json
+    /// {
+    ///     "greeting": "Hello, $name!",
+    ///     "greeting@vars": ["name"]
+    /// }
+    /// ```
+    ///
+    /// Messages without such a key are not checked. Returns pairs of message
+    /// identifier and undeclared variable name, sorted by identifier.
+    /// The same findings are logged as warnings when the locale is loaded.
+    pub fn validate_variables(&self, locale: &Locale) -> Vec<(String, String)> {
+        let mut r = Vec::<(String, String)>::new();
+        if let Some(root) = self._assets.get(locale) {
+            self.collect_undeclared_variables(root, String::new(), &mut r);
+        }
+        r.sort();
+        r
+    }
+
+    fn collect_undeclared_variables(&self, value: &serde_json::Value, prefix: String, output: &mut Vec<(String, String)>) {
+        let map = match value.as_object() {
+            Some(map) => map,
+            None => return,
+        };
+        for (k, v) in map.iter() {
+            let key = join_id(&prefix, k, self._id_separator);
+            let message = match v.as_str() {
+                Some(message) => message,
+                None => {
+                    self.collect_undeclared_variables(v, key, output);
+                    continue;
+                },
+            };
+            let declared = match map.get(&format!("{}@vars", k)).and_then(|vars| vars.as_array()) {
+                Some(declared) => declared,
+                None => continue,
+            };
+            let mut reported = HashSet::<&str>::new();
+            for placeholder in self.placeholder_regex().captures_iter(message) {
+                if let Some((name, _, _)) = MessageLocator::placeholder_parts(&placeholder) {
+                    if !declared.iter().any(|v| v.as_str() == Some(name)) && reported.insert(name) {
+                        output.push((key.clone(), name.to_string()));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Searches the messages of the current locale and its fallbacks for
+    /// identifiers whose last segment matches `partial_id`, ignoring case.
+    /// Returns pairs of full identifier and unformatted message, sorted by
+    /// identifier; a key defined in several locales is reported once,
+    /// with the message that `get` would find.
+    ///
+    /// This is a developer convenience, such as for an interactive console.
+    /// It is O(n) over all loaded keys, so avoid it in production paths.
+    pub fn find(&self, partial_id: &str) -> Vec<(String, String)> {
+        let mut r = Vec::<(String, String)>::new();
+        let partial_id = partial_id.to_lowercase();
+        let mut seen = HashSet::<String>::new();
+        for locale in self._current_locale.iter().flat_map(|locale| self.lookup_chain(locale)) {
+            if let Some(root) = self._assets.get(&locale) {
+                self.for_each_message(root, String::new(), &mut |key, message| {
+                    let last_segment = split_id(&key, self._id_separator).pop().unwrap().to_lowercase();
+                    if last_segment == partial_id && seen.insert(key.clone()) {
+                        r.push((key, message.to_string()));
+                    }
+                });
+            }
+        }
+        r.sort();
+        r
+    }
+
+    /// Searches the messages of the current locale and its fallbacks for
+    /// those whose unformatted value contains `query`, ignoring case and
+    /// diacritics, so that "resume" matches "Résumé". Returns pairs of
+    /// full identifier and message, sorted by identifier; a key defined in
+    /// several locales is reported once, with the message that `get` would find.
+    ///
+    /// This is a linear scan over all loaded messages of those locales,
+    /// which is fine for an in-app help index but should not run per keystroke
+    /// over large catalogs.
+    pub fn search(&self, query: &str) -> Vec<(String, String)> {
+        let mut r = Vec::<(String, String)>::new();
+        let query = fold_for_search(query);
+        let mut seen = HashSet::<String>::new();
+        for locale in self._current_locale.iter().flat_map(|locale| self.lookup_chain(locale)) {
+            if let Some(root) = self._assets.get(&locale) {
+                self.for_each_message(root, String::new(), &mut |key, message| {
+                    if seen.insert(key.clone()) && fold_for_search(message).contains(&query) {
+                        r.push((key, message.to_string()));
+                    }
+                });
+            }
+        }
+        r.sort();
+        r
+    }
+
+    /// Checks the messages loaded for a locale against length budgets,
+    /// given in grapheme clusters per message identifier. Returns pairs of
+    /// message identifier and actual length for the messages that exceed
+    /// their budget, sorted by identifier. Messages are measured unformatted.
+    pub fn validate_lengths(&self, locale: &Locale, budgets: &HashMap<String, usize>) -> Vec<(String, usize)> {
+        let mut r = Vec::<(String, usize)>::new();
+        if let Some(root) = self._assets.get(locale) {
+            self.for_each_message(root, String::new(), &mut |key, message| {
+                if let Some(budget) = budgets.get(&key) {
+                    let length = message.graphemes(true).count();
+                    if length > *budget {
+                        r.push((key, length));
+                    }
+                }
+            });
+        }
+        r.sort();
+        r
+    }
+
+    /// Returns whether a variable value counts as true in conditional contexts.
+    /// A value is false if, trimmed and lowercased, it is one of the falsy values
+    /// given to `MessageLocatorOptions::falsy_values`, by default
+    /// the empty string, `0`, `false` and `no`. Any other value is true.
+    pub fn is_truthy(&self, value: &str) -> bool {
+        !self._falsy_values.contains(&value.trim().to_lowercase())
+    }
+
+    /// Retrieves message by identifier.
+    pub fn get<S: ToString>(&self, id: S) -> String {
+        self.get_formatted(id, vec![])
+    }
+
+    /// Retrieves message by identifier with formatting arguments.
+    ///
+    /// String and number arguments select a contextual variant of the message
+    /// by suffixing the identifier (for example, `qty` with `"one"` resolves `qty_one`).
+    /// Missing variants fall back in two levels:
+    ///
+    /// 1. Within each locale, if the selected variant is absent, the `other`
+    ///    variant (`qty_other`) is tried, then the message itself (`qty`).
+    /// 2. If none is present, the locale fallback chain is searched
+    ///    for the whole message in the same way.
+    ///
+    /// String and number arguments are also available to the message as
+    /// positional variables in order, `$1` being the first of them; for example,
+    /// `greet` with `"Alice"` formats `"Hi $1"` as `"Hi Alice"`. They coexist
+    /// with the named variables of a map argument.
+    ///
+    /// Identifiers are split into segments at each `.`, or at the separator
+    /// set by `MessageLocatorOptions::id_separator`. A literal separator within
+    /// a segment is escaped as `\.` and a literal backslash as `\\`;
+    /// for example, the identifier `files.my\.config.title` (written
+    /// `"files.my\\.config.title"` in Rust source) resolves the `title` message
+    /// inside the `my.config` key of the `files` base file.
+    ///
+    /// Messages may choose between branches with ICU-style select blocks,
+    /// such as for grammatical gender:
+    ///
+    /// ```text
+    /// {gender, select, male {He replied} female {She replied} other {They replied}}
+    /// ```
+    ///
+    /// The branch whose selector equals the value of the variable is used,
+    /// or the `other` branch if none does or the variable is missing. Branches
+    /// may contain placeholders and further select blocks.
+    ///
+    /// Plural blocks choose a branch by the plural category of a numeric
+    /// variable in the locale's language, with `=N` selectors for exact
+    /// numbers and `#` standing for the number:
+    ///
+    /// ```text
+    /// {gender, select, male {{count, plural, =0 {He has no items} one {He has # item} other {He has # items}}} other {...}}
+    /// ```
+    ///
+    /// Select and plural blocks nest to any depth. A plural block whose
+    /// variable is missing or not a number is left as it is.
+    ///
+    /// Number blocks format the value of a variable with the grouping and
+    /// decimal separator of the locale: `{count, number}` as is,
+    /// `{ratio, number, percent}` as a percentage and `{price, number, .2}`
+    /// with two fraction digits. Values that are not numbers are kept as they are.
+    /// Placeholders are substituted afterwards, so a dollar sign before
+    /// a number block is escaped, as in `$${price, number, .2}`.
+    /// Other ICU-style blocks, such as `date`, are not formatted.
+    pub fn get_formatted<S: ToString>(&self, id: S, options: Vec<&dyn MessageLocatorFormatArgument>) -> String {
+        match self.resolve_formatted(id.to_string(), &options) {
+            Ok(r) => r,
+            Err(id) => self.missing(id),
+        }
+    }
+
+    /// Returns the result of a missing message,
+    /// as given by `MessageLocatorOptions::on_missing`.
+    fn missing(&self, id: String) -> String {
+        match self._on_missing.as_ref() {
+            Some(handler) => handler(&id),
+            None => id,
+        }
+    }
+
+    /// Retrieves message by identifier with formatting arguments,
+    /// returning `None` if the message is missing in the current locale
+    /// and its fallbacks, instead of the identifier returned by `get_formatted`.
+    pub fn get_opt<S: ToString>(&self, id: S, options: Vec<&dyn MessageLocatorFormatArgument>) -> Option<String> {
+        self.resolve_formatted(id.to_string(), &options).ok()
+    }
+
+    /// Returns whether a message exists at an identifier in the current locale
+    /// or its fallbacks, following the same fallback traversal as `get`.
+    /// Unlike `get`, this distinguishes a missing message from a message
+    /// whose text equals its identifier.
+    pub fn has_message<S: ToString>(&self, id: S) -> bool {
+        let id = id.to_string();
+        let id = split_id(&if self._case_insensitive_keys { id.to_lowercase() } else { id }, self._id_separator);
+        match self._current_locale.as_ref() {
+            Some(locale) => self.lookup_chain(locale).iter().any(|locale| self.resolve_id(self._assets.get(locale), &id).is_some()),
+            None => false,
+        }
+    }
+
+    /// Returns the front-matter metadata of a base file in the current locale
+    /// or its fallbacks, if `MessageLocatorAssetOptions::front_matter` is enabled
+    /// and the file has any. Nested base file names use the identifier separator,
+    /// as in identifiers.
+    pub fn metadata<S: ToString>(&self, base_name: S) -> Option<serde_json::Value> {
+        self.get_value(format!("{}{}_meta", base_name.to_string(), self._id_separator))
+    }
+
+    /// Retrieves the raw JSON value at an identifier, such as an object
+    /// or an array of locale-specific structured data, from the current
+    /// locale or its fallbacks. Unlike `get`, the value is not required to be
+    /// a string and is returned unformatted. Returns `None` if it is missing.
+    pub fn get_value<S: ToString>(&self, id: S) -> Option<serde_json::Value> {
+        let id = id.to_string();
+        let id = split_id(&if self._case_insensitive_keys { id.to_lowercase() } else { id }, self._id_separator);
+        self.lookup_chain(self._current_locale.as_ref()?).iter()
+            .find_map(|locale| resolve_value(self._assets.get(locale), &id).cloned())
+    }
+
+    /// Retrieves a list of messages by identifier, for keys holding
+    /// an array of strings such as tips to cycle through. Each element is
+    /// formatted as by `get`. Returns an empty list if the key is missing
+    /// in the current locale and its fallbacks, or is not an array of strings.
+    ///
+    /// `get` and `get_formatted` do not resolve arrays and return
+    /// the identifier for such keys.
+    pub fn get_list<S: ToString>(&self, id: S) -> Vec<String> {
+        let id = id.to_string();
+        let id = split_id(&if self._case_insensitive_keys { id.to_lowercase() } else { id }, self._id_separator);
+        let locale = match self._current_locale.as_ref() {
+            Some(locale) => locale,
+            None => return vec![],
+        };
+        let list = self.lookup_chain(locale).iter().find_map(|locale| self.resolve_list(self._assets.get(locale), &id));
+        list.unwrap_or_default().into_iter().map(|message| self.apply_message(locale, message, &HashMap::new())).collect()
+    }
+
+    /// Retrieves message by identifier with formatting arguments, consulting
+    /// the fallback provider given to `MessageLocatorOptions::fallback_provider`
+    /// as a last resort when the message is missing in the current locale and
+    /// its fallbacks. If there is no provider or it returns `None`,
+    /// the identifier is returned, as in `get_formatted`.
+    ///
+    /// The provider's result for each locale and identifier, including `None`,
+    /// is cached for the lifetime of the locator and shared among its clones,
+    /// so only the first lookup of a missing message waits on the provider.
+    pub async fn get_async<S: ToString>(&self, id: S, options: Vec<&dyn MessageLocatorFormatArgument>) -> String {
+        let id = match self.resolve_formatted(id.to_string(), &options) {
+            Ok(r) => return r,
+            Err(id) => id,
+        };
+        let (provider, locale) = match (self._fallback_provider.as_ref(), self._current_locale.as_ref()) {
+            (Some(provider), Some(locale)) => (provider, locale),
+            _ => return self.missing(id),
+        };
+        let cache_key = (locale.clone(), id.clone());
+        let cached = self._provided_messages.lock().unwrap().get(&cache_key).cloned();
+        let provided = match cached {
+            Some(provided) => provided,
+            None => {
+                let split_id = split_id(&id, self._id_separator);
+                let source = self.resolve_id(self._assets.get(&self._default_locale), &split_id);
+                let provided = provider.translate(locale, &id, source.as_deref()).await;
+                self._provided_messages.lock().unwrap().insert(cache_key, provided.clone());
+                provided
+            },
+        };
+        provided.unwrap_or_else(|| self.missing(id))
+    }
+
+    /// Retrieves message by identifier, panicking if it is missing in debug builds.
+    ///
+    /// In debug builds, a missing message panics with
+    /// `missing translation key: <id> in locale <current>`, making missing
+    /// translations visible during development. In release builds,
+    /// the identifier is returned, as in `get`.
+    pub fn get_required<S: ToString>(&self, id: S) -> String {
+        match self.resolve_formatted(id.to_string(), &[]) {
+            Ok(r) => r,
+            Err(id) => {
+                if cfg!(debug_assertions) {
+                    let locale = self._current_locale.as_ref().map(|l| l.standard_tag().to_string()).unwrap_or_else(|| String::from("(none)"));
+                    panic!("missing translation key: {} in locale {}", id, locale);
+                }
+                id
+            },
+        }
+    }
+
+    /// Retrieves the plural form of a message for a count. The message
+    /// identifier names an object whose keys are CLDR plural categories
+    /// (`zero`, `one`, `two`, `few`, `many` and `other`); for example,
+    /// `inbox.messages` with count 5 resolves `inbox.messages.other` in English.
+    ///
+    /// The category is selected by the plural rules of the current locale's
+    /// language. If the locale lacks the selected category, or its language
+    /// has no known rules, `other` is used. The count is available to
+    /// the message as the `$count` variable, along with any variables
+    /// in `options`. As with `get_formatted`, the locale fallback chain
+    /// is searched, and the identifier is returned if no message is found.
+    pub fn get_plural<S: ToString>(&self, id: S, count: i64, options: Vec<&dyn MessageLocatorFormatArgument>) -> String {
+        self.get_plural_decimal(id, &count.to_string(), options)
+    }
+
+    /// Retrieves the plural form of a message for a count written as a string,
+    /// as `get_plural` does. Fraction digits that are written count, even if
+    /// they are zero: in English, `1` selects `one` but `1.0` selects `other`.
+    /// The count may also be a range, as in `1-2`, which selects the category
+    /// of its end. The count is available to the message as the `$count`
+    /// variable as given, and can be formatted with the `range` formatter.
+    /// A count that is not a number or range selects `other`.
+    pub fn get_plural_decimal<S: ToString>(&self, id: S, count: &str, options: Vec<&dyn MessageLocatorFormatArgument>) -> String {
+        let id = id.to_string();
+        let locale = match self._current_locale.as_ref() {
+            Some(locale) => locale,
+            None => return self.missing(id),
+        };
+        let category = plural_rules::plural_category_of(&locale.language(), count).unwrap_or(plural_rules::PluralCategory::Other);
+        let mut variables = self.format_variables(self._current_locale.as_ref(), &options);
+        variables.insert(String::from("count"), count.to_string());
+
+        let lookup_id = if self._case_insensitive_keys { id.to_lowercase() } else { id.clone() };
+        let mut ids: Vec<Vec<String>> = vec![split_id(&format!("{}{}{}", lookup_id, self._id_separator, category.as_str()), self._id_separator)];
+        if category != plural_rules::PluralCategory::Other {
+            ids.push(split_id(&format!("{}{}other", lookup_id, self._id_separator), self._id_separator));
+        }
+        match self.get_formatted_with_locale(locale.clone(), &ids, &variables) {
+            Some((r, _)) => r,
+            None => self.missing(id),
+        }
+    }
+
+    /// Collects the variables of formatting arguments, localizing them
+    /// for `locale`, which is usually the current locale.
+    fn format_variables(&self, locale: Option<&Locale>, options: &[&dyn MessageLocatorFormatArgument]) -> HashMap<String, String> {
+        let mut variables = HashMap::<String, String>::new();
+        for option in options.iter() {
+            if option.as_str().is_none() && option.as_string().is_none() {
+                if let Some(r) = option.as_string_map() {
+                    variables = r.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+                }
+            }
+        }
+        let positional = options.iter().filter_map(|option| option.as_str().map(|s| s.to_string())
+            .or_else(|| option.as_string())
+            .or_else(|| locale.and_then(|locale| option.as_localized_string(locale))));
+        for (i, v) in positional.enumerate() {
+            variables.insert((i + 1).to_string(), v);
+        }
+        if let Some(locale) = locale {
+            for option in options.iter() {
+                if let Some((k, v)) = option.as_localized_var(locale) {
+                    variables.insert(k, v);
+                }
+            }
+        }
+        variables
+    }
+
+    /// Retrieves message by identifier like `get`, truncated to at most
+    /// `max_graphemes` grapheme clusters. A truncated message ends with
+    /// an ellipsis (`…`), which counts towards the limit, so that
+    /// user-perceived characters such as emoji or letters with combining
+    /// marks are never split.
+    pub fn get_truncated<S: ToString>(&self, id: S, max_graphemes: usize) -> String {
+        let message = self.get(id);
+        if message.graphemes(true).count() <= max_graphemes {
+            return message;
+        }
+        if max_graphemes == 0 {
+            return String::new();
+        }
+        let mut r: String = message.graphemes(true).take(max_graphemes - 1).collect();
+        r.push('…');
+        r
+    }
+
+    /// Retrieves message by identifier with formatting arguments, filling only
+    /// the variables that are given. Returns the message, with placeholders of
+    /// missing variables left intact, along with the names of those variables
+    /// in order of appearance. This suits multi-stage pipelines, where a later
+    /// stage provides the remaining variables.
+    ///
+    /// If the message is missing, the identifier is returned with no names,
+    /// as in `get_formatted`.
+    pub fn get_partial<S: ToString>(&self, id: S, options: Vec<&dyn MessageLocatorFormatArgument>) -> (String, Vec<String>) {
+        match self.lookup_message(id.to_string(), &options) {
+            Ok((message, variables)) => {
+                let mut unfilled = Vec::<String>::new();
+                let r = self.interpolate(self._current_locale.as_ref().unwrap(), message, &variables, Some(&mut unfilled), 0, &mut 0);
+                (r, unfilled)
+            },
+            Err(id) => (id, vec![]),
+        }
+    }
+
+    /// Resolves and formats a message, returning the full identifier
+    /// as the error if it is missing.
+    fn resolve_formatted(&self, id: String, options: &[&dyn MessageLocatorFormatArgument]) -> Result<String, String> {
+        self.lookup_message(id, options).map(|(message, variables)| self.apply_message(self._current_locale.as_ref().unwrap(), message, &variables))
+    }
+
+    /// Retrieves message by identifier with formatting arguments like
+    /// `get_formatted`, along with the locale whose assets define it,
+    /// which is either the current locale or one of its fallbacks.
+    /// Returns `None` if the message is missing.
+    pub fn get_with_source<S: ToString>(&self, id: S, options: Vec<&dyn MessageLocatorFormatArgument>) -> Option<(String, Locale)> {
+        let (_, ids, variables) = self.message_ids(id.to_string(), self._current_locale.as_ref(), &options);
+        self.get_formatted_with_locale(self._current_locale.clone()?, &ids, &variables)
+    }
+
+    /// Retrieves message by identifier with formatting arguments like
+    /// `get_formatted`, but in the given locale and its fallbacks instead of
+    /// the current locale, which is left unchanged. This is useful to render
+    /// a message in another language, such as an email in the recipient's
+    /// language. Formatting arguments are localized for the given locale.
+    ///
+    /// The assets of the locale must have been loaded or preloaded beforehand;
+    /// otherwise the message is treated as missing, as in `get_formatted`.
+    /// See `get_in_loading` to load them on demand.
+    pub fn get_in<S: ToString>(&self, locale: &Locale, id: S, options: Vec<&dyn MessageLocatorFormatArgument>) -> String {
+        let locale = self.canonical_locale(locale);
+        let (id, ids, variables) = self.message_ids(id.to_string(), Some(&locale), &options);
+        match self.get_formatted_with_locale(locale, &ids, &variables) {
+            Some((r, _)) => r,
+            None => self.missing(id),
+        }
+    }
+
+    /// Retrieves message by identifier with formatting arguments in the given
+    /// locale like `get_in`, first loading the locale and its fallbacks into
+    /// the cache, as `preload` does, if they are not loaded yet. This suits
+    /// applications that preload their main locales at startup and load
+    /// the rest when first requested. The current locale is left unchanged.
+    ///
+    /// If loading fails, the error is logged as a warning and the message
+    /// is looked up in what is already loaded.
+    pub async fn get_in_loading<S: ToString>(&mut self, locale: &Locale, id: S, options: Vec<&dyn MessageLocatorFormatArgument>) -> String {
+        if let Err(error) = self.preload(std::slice::from_ref(locale)).await {
+            log::warn!("{}", error);
+        }
+        self.get_in(locale, id, options)
+    }
+
+    /// Retrieves message by identifier with formatting arguments like
+    /// `get_formatted`, but searching only the given locales, in order,
+    /// instead of the current locale and its configured fallbacks. This suits
+    /// screens that need a different fallback order, such as a legal page
+    /// that falls back to the source locale. The configuration is not changed.
+    ///
+    /// The locales must have been loaded or preloaded beforehand;
+    /// the others are skipped. If no message is found, the identifier is returned.
+    pub fn get_formatted_with_fallbacks<S: ToString>(&self, id: S, locales: &[Locale], options: Vec<&dyn MessageLocatorFormatArgument>) -> String {
+        let (id, ids, variables) = self.message_ids(id.to_string(), self._current_locale.as_ref(), &options);
+        for locale in locales.iter() {
+            for id in ids.iter() {
+                if let Some(message) = self.resolve_id(self._assets.get(locale), id) {
+                    return self.apply_message(locale, message, &variables);
+                }
+            }
+        }
+        self.missing(id)
+    }
+
+    /// Formats a message string directly, substituting the given variables
+    /// as if the message were loaded in `locale`, without looking up any
+    /// identifier. Formatters that depend on the locale, such as `case`,
+    /// use the assets loaded for `locale` and its fallbacks.
+    ///
+    /// This exposes the formatter used by `get_formatted`, such as for
+    /// checking output against another system's reference strings.
+    /// Contexts are selected by message identifier, not within the message,
+    /// so only `select`, `plural` and `number` blocks are formatted,
+    /// as described in `get_formatted`.
+    pub fn format_message(&self, locale: &Locale, message: &str, vars: &HashMap<String, String>) -> String {
+        self.apply_message(locale, message.to_string(), vars)
+    }
+
+    /// Finds the unformatted message for an identifier with formatting arguments,
+    /// returning it along with its variables, or the identifier if it is missing.
+    fn lookup_message(&self, id: String, options: &[&dyn MessageLocatorFormatArgument]) -> Result<(String, HashMap<String, String>), String> {
+        let (id, ids, variables) = self.message_ids(id, self._current_locale.as_ref(), options);
+        if self._current_locale.is_none() {
+            return Err(id);
+        }
+        let r = self.find_message(self._current_locale.clone().unwrap(), &ids);
+        if let Some((r, _)) = r { Ok((r, variables)) } else { Err(id) }
+    }
+
+    /// Returns the identifier with context applied, the split identifiers
+    /// to search in order and the variables of the formatting arguments.
+    fn message_ids(&self, id: String, locale: Option<&Locale>, options: &[&dyn MessageLocatorFormatArgument]) -> (String, Vec<Vec<String>>, HashMap<String, String>) {
+        let base_id = id.clone();
+        let mut id = id;
+        let mut id_before_context: Option<String> = None;
+
+        for option in options.iter() {
+            if let Some(r) = option.as_str() {
+                id_before_context = Some(id.clone());
+                id.push('_');
+                id.push_str(r);
+            }
+            else if let Some(r) = option.as_string() {
+                id_before_context = Some(id.clone());
+                id.push('_');
+                id.push_str(r.as_str());
+            }
+        }
+        let variables = self.format_variables(locale, options);
+
+        let lookup_id = if self._case_insensitive_keys { id.to_lowercase() } else { id.clone() };
+        let mut ids: Vec<Vec<String>> = vec![split_id(&lookup_id, self._id_separator)];
+        if let Some(base) = id_before_context {
+            let other = format!("{}_other", base);
+            let other = if self._case_insensitive_keys { other.to_lowercase() } else { other };
+            if other != lookup_id {
+                ids.push(split_id(&other, self._id_separator));
+            }
+            let base = if self._case_insensitive_keys { base_id.to_lowercase() } else { base_id };
+            if base != lookup_id {
+                ids.push(split_id(&base, self._id_separator));
+            }
+        }
+        (id, ids, variables)
+    }
+
+    fn get_formatted_with_locale(&self, locale: Locale, ids: &[Vec<String>], vars: &HashMap<String, String>) -> Option<(String, Locale)> {
+        self.find_message(locale.clone(), ids).map(|(message, source)| (self.apply_message(&locale, message, vars), source))
+    }
+
+    /// Finds the unformatted message for the first of `ids` defined in
+    /// the locale or its fallbacks, along with the locale that defines it.
+    fn find_message(&self, locale: Locale, ids: &[Vec<String>]) -> Option<(String, Locale)> {
+        for locale in self.lookup_chain(&locale) {
+            for id in ids.iter() {
+                if let Some(message) = self.resolve_id(self._assets.get(&locale), id) {
+                    return Some((message, locale));
+                }
+            }
+        }
+        None
+    }
+
+    fn apply_message(&self, locale: &Locale, message: String, vars: &HashMap<String, String>) -> String {
+        let escaped_braces = self._placeholder_style == PlaceholderStyle::Braces;
+        let message = message_syntax::apply_selects(&message, vars, escaped_braces, &locale.language());
+        let message = message_syntax::apply_numbers(&message, vars, &number_format::number_symbols(&locale.language()), escaped_braces);
+        self.interpolate(locale, message, vars, None, 0, &mut 0)
+    }
+
+    /// Returns the pattern of placeholders in the configured style.
+    /// See `placeholder_parts` for reading its matches. In the dollar style,
+    /// `$$` and `\$` escape a dollar sign and `\\` escapes a backslash.
+    fn placeholder_regex(&self) -> &'static regex::Regex {
+        match self._placeholder_style {
+            PlaceholderStyle::Dollar => regex!(r"\\[\\$]|\$\$|\$(?P<name>[A-Za-z0-9_-]+)(?P<filters>(?:\|[A-Za-z0-9_-]+(?::(?:\\.|[A-Za-z0-9_-])*)?)*)|\$\{(?P<bname>[A-Za-z0-9_-]+)(?P<bfilters>(?:\|[A-Za-z0-9_-]+(?::(?:\\.|[A-Za-z0-9_-])*)?)*)(?::(?P<default>[^}]*))?\}"),
+            PlaceholderStyle::Braces => regex!(r"\{\{|\}\}|\{(?P<name>[A-Za-z0-9_-]+)(?P<filters>(?:\|[A-Za-z0-9_-]+(?::(?:\\.|[A-Za-z0-9_-])*)?)*)(?::(?P<default>[^}]*))?\}"),
+        }
+    }
+
+    /// Returns the variable name, filters and default value of
+    /// a placeholder match, or `None` for an escape.
+    fn placeholder_parts<'t>(placeholder: &regex::Captures<'t>) -> Option<(&'t str, &'t str, Option<&'t str>)> {
+        let name = placeholder.name("name").or_else(|| placeholder.name("bname"))?.as_str();
+        let filters = placeholder.name("filters").or_else(|| placeholder.name("bfilters")).map_or("", |f| f.as_str());
+        Some((name, filters, placeholder.name("default").map(|d| d.as_str())))
+    }
+
+    /// Substitutes placeholders in a message. Placeholders of missing variables
+    /// format as their default value or `undefined`, unless `unfilled` is given,
+    /// in which case they are left intact and their names are collected.
+    /// `depth` counts the variable values being substituted within, and
+    /// `steps` counts the substitutions and formatters applied so far across
+    /// them, bounded by `MessageLocatorOptions::max_format_steps`.
+    fn interpolate(&self, locale: &Locale, message: String, vars: &HashMap<String, String>, mut unfilled: Option<&mut Vec<String>>, depth: usize, steps: &mut usize) -> String {
+        let placeholder = self.placeholder_regex();
+        let isolate = self._bidi_isolation && self._direction_override.unwrap_or_else(|| locale.direction()) == Direction::RightToLeft;
+        placeholder.replace_all(&message, |s: &regex::Captures<'_>| {
+            let (name, filters, default) = match MessageLocator::placeholder_parts(s) {
+                Some(parts) => parts,
+                None => return s.get(0).unwrap().as_str()[1..].to_string(),
+            };
+            let filter_text = filters;
+            let mut filters: Vec<regex::Captures<'_>> = regex!(r"\|([A-Za-z0-9_-]+)(?::((?:\\.|[A-Za-z0-9_-])*))?").captures_iter(filter_text).collect();
+            // text follows a bare `$name`, so a pipe that does not start
+            // a known formatter, as in `$name|word`, is kept as text
+            let mut rest = "";
+            if self._placeholder_style == PlaceholderStyle::Dollar && s.name("name").is_some() {
+                if let Some(unknown) = filters.iter().position(|f| !self.has_formatter(f.get(1).unwrap().as_str())) {
+                    rest = &filter_text[filters[unknown].get(0).unwrap().start()..];
+                    filters.truncate(unknown);
+                }
+            }
+            *steps += 1 + filters.len();
+            if *steps > self._max_format_steps {
+                return s.get(0).unwrap().as_str().to_string();
+            }
+            let v = vars.get(name);
+            if v.is_none() {
+                if let Some(unfilled) = unfilled.as_mut() {
+                    if !unfilled.iter().any(|n| n == name) {
+                        unfilled.push(name.to_string());
+                    }
+                    return s.get(0).unwrap().as_str().to_string();
+                }
+                return format!("{}{}", default.unwrap_or("undefined"), rest);
+            }
+            let mut v = v.unwrap().clone();
+            if self._recursive_interpolation && depth < 8 {
+                v = self.interpolate(locale, v, vars, unfilled.as_deref_mut(), depth + 1, steps);
+            }
+            for f in filters {
+                let arg = f.get(2).map(|arg| regex!(r"\\(.)").replace_all(arg.as_str(), "$1").to_string());
+                v = self.apply_formatter(locale, f.get(1).unwrap().as_str(), &v, arg.as_deref());
+            }
+            if isolate { format!("\u{2068}{}\u{2069}{}", v, rest) } else { v + rest }
+        }).as_ref().to_string()
+    }
+
+    /// Indicates whether a formatter is registered or built in.
+    fn has_formatter(&self, name: &str) -> bool {
+        self._formatters.contains_key(name)
+            || ["upper", "lower", "currency", "percent", "permille", "range", "case"].contains(&name)
+            || (cfg!(feature = "datetime") && name == "format")
+    }
+
+    fn apply_formatter(&self, locale: &Locale, name: &str, value: &str, arg: Option<&str>) -> String {
+        if let Some(f) = self._formatters.get(name) {
+            return f(value, arg);
+        }
+        match name {
+            "upper" => value.to_uppercase(),
+            "lower" => value.to_lowercase(),
+            "currency" => {
+                let n = match value.parse::<f64>() {
+                    Ok(n) => n,
+                    Err(_) => return value.to_string(),
+                };
+                let n = number_format::format_decimal(n, 2, &number_format::number_symbols(&locale.language()));
+                if let Some(code) = arg { format!("{} {}", n, code) } else { n }
+            },
+            "percent" | "permille" => {
+                let n = match value.parse::<f64>() {
+                    Ok(n) => n,
+                    Err(_) => return value.to_string(),
+                };
+                let fraction_digits = arg.and_then(|arg| arg.parse::<usize>().ok()).unwrap_or(0);
+                let symbols = number_format::number_symbols(&locale.language());
+                let (scale, sign) = if name == "percent" { (100.0, "%") } else { (1000.0, "‰") };
+                number_format::format_scaled(n, scale, fraction_digits, sign, &symbols)
+            },
+            #[cfg(feature = "datetime")]
+            "format" => datetime::format_date_str(value, arg, locale).unwrap_or_else(|| value.to_string()),
+            "range" => {
+                number_format::format_range(value.trim(), &number_format::number_symbols(&locale.language())).unwrap_or_else(|| value.to_string())
+            },
+            "case" => {
+                let case = match arg {
+                    Some(case) => case,
+                    None => return value.to_string(),
+                };
+                let mut id = self._declension_tables.clone();
+                id.push(value.to_string());
+                id.push(case.to_string());
+                if self._case_insensitive_keys {
+                    id = id.into_iter().map(|s| s.to_lowercase()).collect();
+                }
+                let form = self.lookup_chain(locale).iter().find_map(|locale| self.resolve_id(self._assets.get(locale), &id));
+                form.unwrap_or_else(|| value.to_string())
+            },
+            _ => value.to_string(),
+        }
+    }
+
+    fn resolve_id(&self, root: Option<&serde_json::Value>, id: &[String]) -> Option<String> {
+        let r = resolve_value(root, id)?.as_str();
+        if let Some(r) = r { Some(r.to_string()) } else { None }
+    }
+
+    fn resolve_list(&self, root: Option<&serde_json::Value>, id: &[String]) -> Option<Vec<String>> {
+        resolve_value(root, id)?.as_array()?.iter().map(|v| v.as_str().map(|s| s.to_string())).collect()
+    }
+}
+
+impl Clone for MessageLocator {
+    /// Clones the locator, sharing the same
+    /// resources. Loading through one of the copies afterwards
+    /// copies the loaded assets first, leaving the other unchanged.
+    fn clone(&self) -> Self {
+        Self {
+            _current_locale: self._current_locale.clone(),
+            _locale_path_components: self._locale_path_components.clone(),
+            _supported_locales: self._supported_locales.clone(),
+            _default_locale: self._default_locale.clone(),
+            _fallbacks: self._fallbacks.clone(),
+            _aliases: self._aliases.clone(),
+            _assets: self._assets.clone(),
+            _partial_locales: self._partial_locales.clone(),
+            _assets_src: self._assets_src.clone(),
+            _assets_base_file_names: self._assets_base_file_names.clone(),
+            _assets_clean_unused: self._assets_clean_unused,
+            _assets_load_via: self._assets_load_via,
+            _assets_memory: self._assets_memory.clone(),
+            _assets_file_names: self._assets_file_names.clone(),
+            _assets_static: self._assets_static.clone(),
+            _assets_loader: self._assets_loader.clone(),
+            _assets_shared_base_file_names: self._assets_shared_base_file_names.clone(),
+            _assets_shared_src: self._assets_shared_src.clone(),
+            _assets_format: self._assets_format,
+            _assets_inherit_language_files: self._assets_inherit_language_files,
+            _assets_front_matter: self._assets_front_matter,
+            _assets_merge_strategy: self._assets_merge_strategy,
+            _assets_single_file: self._assets_single_file,
+            _assets_compression: self._assets_compression,
+            _assets_response_transform: self._assets_response_transform.clone(),
+            _http_client: self._http_client.clone(),
+            _http_retries: self._http_retries,
+            _http_retry_backoff: self._http_retry_backoff,
+            _http_cache_dir: self._http_cache_dir.clone(),
+            _reload_interval: self._reload_interval,
+            _case_insensitive_keys: self._case_insensitive_keys,
+            _implicit_language_fallback: self._implicit_language_fallback,
+            _placeholder_style: self._placeholder_style,
+            _max_format_steps: self._max_format_steps,
+            _bidi_isolation: self._bidi_isolation,
+            _recursive_interpolation: self._recursive_interpolation,
+            _id_separator: self._id_separator,
+            _declension_tables: self._declension_tables.clone(),
+            _key_sources: self._key_sources.clone(),
+            _formatters: self._formatters.clone(),
+            _falsy_values: self._falsy_values.clone(),
+            _fallback_provider: self._fallback_provider.clone(),
+            _on_missing: self._on_missing.clone(),
+            _provided_messages: self._provided_messages.clone(),
+            _direction_override: self._direction_override,
+            _locale_change_listeners: self._locale_change_listeners.clone(),
+        }
+    }
+}
+
+/// Structural difference between two versions of a locale's assets,
+/// as computed by `diff_assets`. Keys are dotted message identifiers,
+/// in which `.` and `\` within a key are escaped with a backslash;
+/// leaves are any values other than non-empty objects.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct AssetDiff {
+    pub added: HashMap<String, serde_json::Value>,
+    pub removed: HashMap<String, serde_json::Value>,
+    /// Changed leaves, mapped to their old and new values.
+    pub changed: HashMap<String, (serde_json::Value, serde_json::Value)>,
+}
+
+impl AssetDiff {
+    /// Returns `true` if the diff contains no changes.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Computes the added, removed and changed leaves between two asset trees.
+pub fn diff_assets(old: &serde_json::Value, new: &serde_json::Value) -> AssetDiff {
+    let mut old_leaves = HashMap::<String, serde_json::Value>::new();
+    let mut new_leaves = HashMap::<String, serde_json::Value>::new();
+    collect_leaves(old, String::new(), &mut old_leaves);
+    collect_leaves(new, String::new(), &mut new_leaves);
+    let mut r = AssetDiff::default();
+    for (k, v) in old_leaves.iter() {
+        match new_leaves.get(k) {
+            None => { r.removed.insert(k.clone(), v.clone()); },
+            Some(new_v) if new_v != v => { r.changed.insert(k.clone(), (v.clone(), new_v.clone())); },
+            _ => {},
+        }
+    }
+    for (k, v) in new_leaves {
+        if !old_leaves.contains_key(&k) {
+            r.added.insert(k, v);
+        }
+    }
+    r
+}
 
-        true
+/// Applies a diff computed by `diff_assets` to an asset tree, so that
+/// `apply_patch(&mut old, &diff_assets(&old, &new))` turns `old` into `new`.
+pub fn apply_patch(root: &mut serde_json::Value, diff: &AssetDiff) {
+    let split = |k: &str| split_id(k, '.');
+    for k in diff.removed.keys() {
+        let id = split(k);
+        remove_leaf(root, &id.iter().map(|s| s.as_str()).collect::<Vec<&str>>());
+    }
+    for (k, v) in diff.added.iter().chain(diff.changed.iter().map(|(k, (_, v))| (k, v))) {
+        let id = split(k);
+        set_leaf(root, &id.iter().map(|s| s.as_str()).collect::<Vec<&str>>(), v.clone());
     }
+}
 
-    async fn load_single_locale(&self, locale: &Locale) -> Option<serde_json::Value> {
-        let mut r = serde_json::Value::Object(serde_json::Map::new());
-        match self._assets_load_via {
-            MessageLocatorLoadVia::FileSystem => {
-                for base_name in self._assets_base_file_names.iter() {
-                    let locale_path_comp = self._locale_path_components.get(locale);
-                    if locale_path_comp.is_none() {
-                        panic!("Fallback locale is not supported a locale: {}", locale.standard_tag().to_string());
-                    }
-                    let res_path = format!("{}/{}/{}.json", self._assets_src, locale_path_comp.unwrap(), base_name);
-                    let content = std::fs::read(res_path.clone());
-                    if content.is_err() {
-                        println!("Failed to load resource at {}.", res_path);
-                        return None;
-                    }
-                    MessageLocator::apply_deep(base_name, serde_json::from_str(String::from_utf8(content.unwrap()).unwrap().as_ref()).unwrap(), &mut r);
-                }
-            },
-            MessageLocatorLoadVia::Http => {
-                for base_name in self._assets_base_file_names.iter() {
-                    let locale_path_comp = self._locale_path_components.get(locale);
-                    if locale_path_comp.is_none() {
-                        panic!("Fallback locale is not supported a locale: {}", locale.standard_tag().to_string());
-                    }
-                    let res_path = format!("{}/{}/{}.json", self._assets_src, locale_path_comp.unwrap(), base_name);
-                    let content = reqwest::get(reqwest::Url::parse(res_path.clone().as_ref()).unwrap()).await;
-                    if content.is_err() {
-                        println!("Failed to load resource at {}.", res_path);
-                        return None;
-                    }
-                    let content = if content.is_ok() { Some(content.unwrap().text().await) } else { None };
-                    MessageLocator::apply_deep(base_name, serde_json::from_str(content.unwrap().unwrap().as_ref()).unwrap(), &mut r);
-                }
-            },
+fn resolve_value<'a>(root: Option<&'a serde_json::Value>, id: &[String]) -> Option<&'a serde_json::Value> {
+    let mut r = root;
+    for frag in id.iter() {
+        r = r?.get(frag);
+    }
+    r
+}
+
+/// Splits a leading YAML front-matter block, delimited by `---` lines,
+/// from the rest of a file.
+fn split_front_matter(content: &[u8]) -> (Option<&str>, &[u8]) {
+    let text = match std::str::from_utf8(content) {
+        Ok(text) => text,
+        Err(_) => return (None, content),
+    };
+    let rest = match text.strip_prefix("---\n").or_else(|| text.strip_prefix("---\r\n")) {
+        Some(rest) => rest,
+        None => return (None, content),
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return (Some(&rest[..offset]), &rest.as_bytes()[offset + line.len()..]);
         }
-        Some(r)
+        offset += line.len();
     }
+    (None, content)
+}
 
-    fn apply_deep(name: &String, assign: serde_json::Value, mut output: &mut serde_json::Value) {
-        let mut names: Vec<&str> = name.split("/").collect();
-        let last_name = names.pop();
-        for name in names {
-            let r = output.get(name);
-            if r.is_none() || r.unwrap().as_object().is_none() {
-                let r = serde_json::Value::Object(serde_json::Map::new());
-                output.as_object_mut().unwrap().insert(String::from(name), r);
-            }
-            output = output.get_mut(name).unwrap();
+/// Splits a message identifier at unescaped occurrences of `separator`,
+/// unescaping the separator and `\\` preceded by a backslash.
+fn split_id(id: &str, separator: char) -> Vec<String> {
+    let mut r = vec![String::new()];
+    let mut chars = id.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => match chars.next() {
+                Some(next) if next == separator || next == '\\' => r.last_mut().unwrap().push(next),
+                Some(next) => {
+                    r.last_mut().unwrap().push(ch);
+                    r.last_mut().unwrap().push(next);
+                },
+                None => r.last_mut().unwrap().push(ch),
+            },
+            _ if ch == separator => r.push(String::new()),
+            _ => r.last_mut().unwrap().push(ch),
         }
-        output.as_object_mut().unwrap().insert(String::from(last_name.unwrap()), assign);
     }
+    r
+}
 
-    fn enumerate_fallbacks(&self, locale: Locale, output: &mut HashSet<Locale>) {
-        for list in self._fallbacks.get(&locale).iter() {
-            for item in list.iter() {
-                output.insert(item.clone());
-                self.enumerate_fallbacks(item.clone(), output);
-            }
+/// Appends `key` to the message identifier `prefix`, escaping
+/// `\` and `separator` in `key` as `split_id` expects them.
+fn join_id(prefix: &str, key: &str, separator: char) -> String {
+    let mut r = String::from(prefix);
+    if !prefix.is_empty() {
+        r.push(separator);
+    }
+    for ch in key.chars() {
+        if ch == separator || ch == '\\' {
+            r.push('\\');
         }
+        r.push(ch);
     }
+    r
+}
 
-    /// Retrieves message by identifier.
-    pub fn get<S: ToString>(&self, id: S) -> String {
-        self.get_formatted(id, vec![])
+/// Requests a resource with the `fetch` API of the browser window,
+/// returning a description of the failure as the error.
+#[cfg(feature = "wasm")]
+async fn fetch_in_browser(url: &str) -> Result<Vec<u8>, String> {
+    use wasm_bindgen::JsCast;
+    let describe = |error: wasm_bindgen::JsValue| error.as_string().unwrap_or_else(|| format!("{:?}", error));
+    let window = web_sys::window().ok_or_else(|| String::from("No browser window"))?;
+    let response = wasm_bindgen_futures::JsFuture::from(window.fetch_with_str(url)).await.map_err(describe)?;
+    let response: web_sys::Response = response.dyn_into().map_err(describe)?;
+    if !response.ok() {
+        return Err(format!("HTTP status {}", response.status()));
     }
+    let buffer = wasm_bindgen_futures::JsFuture::from(response.array_buffer().map_err(describe)?).await.map_err(describe)?;
+    Ok(js_sys::Uint8Array::new(&buffer).to_vec())
+}
 
-    /// Retrieves message by identifier with formatting arguments.
-    pub fn get_formatted<S: ToString>(&self, id: S, options: Vec<&dyn MessageLocatorFormatArgument>) -> String {
-        let mut variables: Option<HashMap<String, String>> = None;
-        let mut id = id.to_string();
+/// Folds text for `MessageLocator::search`: decomposes it,
+/// strips combining marks and lowercases it.
+fn fold_for_search(text: &str) -> String {
+    text.nfd().filter(|c| !is_combining_mark(*c)).collect::<String>().to_lowercase()
+}
 
-        for option in options.iter() {
-            if let Some(r) = option.as_str() {
-                id.push('_');
-                id.push_str(r);
+/// Merges `overlay` into `target` key by key, replacing any non-object value.
+fn merge_deep(target: &mut serde_json::Value, overlay: serde_json::Value) {
+    match (target, overlay) {
+        (serde_json::Value::Object(target), serde_json::Value::Object(overlay)) => {
+            for (k, v) in overlay {
+                match target.get_mut(&k) {
+                    Some(existing) => merge_deep(existing, v),
+                    None => { target.insert(k, v); },
+                }
             }
-            else if let Some(r) = option.as_string() {
-                id.push('_');
-                id.push_str(r.as_str());
+        },
+        (target, overlay) => *target = overlay,
+    }
+}
+
+fn collect_leaves(value: &serde_json::Value, prefix: String, output: &mut HashMap<String, serde_json::Value>) {
+    match value {
+        serde_json::Value::Object(map) if !map.is_empty() => {
+            for (k, v) in map.iter() {
+                collect_leaves(v, join_id(&prefix, k, '.'), output);
             }
-            else if let Some(r) = option.as_string_map() {
-                variables = Some(r.iter().map(|(k, v)| (k.clone(), v.clone())).collect());
+        },
+        _ => {
+            if !prefix.is_empty() {
+                output.insert(prefix, value.clone());
             }
-        }
-
-        if variables.is_none() { variables = Some(HashMap::new()); }
-        let variables = variables.unwrap();
+        },
+    }
+}
 
-        let id: Vec<String> = id.split(".").map(|s| s.to_string()).collect();
-        if self._current_locale.is_none() {
-            return id.join(".");
+fn set_leaf(mut output: &mut serde_json::Value, id: &[&str], value: serde_json::Value) {
+    let (last, names) = id.split_last().unwrap();
+    for name in names {
+        if output.get(*name).and_then(|v| v.as_object()).is_none() {
+            output.as_object_mut().unwrap().insert(name.to_string(), serde_json::Value::Object(serde_json::Map::new()));
         }
-        let r = self.get_formatted_with_locale(self._current_locale.clone().unwrap(), &id, &variables);
-        if let Some(r) = r { r } else { id.join(".") }
+        output = output.get_mut(*name).unwrap();
+    }
+    if !output.is_object() {
+        *output = serde_json::Value::Object(serde_json::Map::new());
     }
+    output.as_object_mut().unwrap().insert(last.to_string(), value);
+}
 
-    fn get_formatted_with_locale(&self, locale: Locale, id: &Vec<String>, vars: &HashMap<String, String>) -> Option<String> {
-        let message = self.resolve_id(self._assets.get(&locale), id);
-        if message.is_some() {
-            return Some(self.apply_message(message.unwrap(), vars));
+/// Removes a leaf and the objects left empty by its removal.
+fn remove_leaf(output: &mut serde_json::Value, id: &[&str]) {
+    let (first, rest) = id.split_first().unwrap();
+    let map = output.as_object_mut();
+    if map.is_none() {
+        return;
+    }
+    let map = map.unwrap();
+    if rest.is_empty() {
+        map.remove(*first);
+        return;
+    }
+    if let Some(child) = map.get_mut(*first) {
+        remove_leaf(child, rest);
+        if child.as_object().map(|c| c.is_empty()).unwrap_or(false) {
+            map.remove(*first);
         }
+    }
+}
 
-        let fallbacks = self._fallbacks.get(&locale);
-        if fallbacks.is_some() {
-            for fl in fallbacks.unwrap().iter() {
-                let r = self.get_formatted_with_locale(fl.clone(), id, vars);
-                if r.is_some() {
-                    return r;
-                }
+/// How a message identifier resolved, as traced by `MessageLocator::explain`.
+#[derive(Clone)]
+pub struct ResolutionTrace {
+    pub id: String,
+    /// The locales searched, in order, up to the one defining the message.
+    pub steps: Vec<ResolutionStep>,
+    /// The unformatted message chosen, or `None` if it is missing.
+    pub message: Option<String>,
+}
+
+/// A locale searched while resolving a message. See `ResolutionTrace`.
+#[derive(Clone)]
+pub struct ResolutionStep {
+    pub locale: Locale,
+    /// Whether the locale defines the message.
+    pub found: bool,
+    /// The base file that provided the message, if tracked.
+    pub source: Option<String>,
+}
+
+impl std::fmt::Display for ResolutionTrace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}:", self.id)?;
+        for step in self.steps.iter() {
+            let tag = step.locale.standard_tag().to_string();
+            match (step.found, step.source.as_ref()) {
+                (true, Some(source)) => writeln!(f, "  {}: found in {}", tag, source)?,
+                (true, None) => writeln!(f, "  {}: found", tag)?,
+                (false, _) => writeln!(f, "  {}: not found", tag)?,
             }
         }
-        None
+        match self.message.as_ref() {
+            Some(message) => write!(f, "  => {:?}", message),
+            None => write!(f, "  => missing"),
+        }
     }
+}
 
-    fn apply_message(&self, message: String, vars: &HashMap<String, String>) -> String {
-        // regex!(r"\$(\$|[A-Za-z0-9_-]+)").replace_all(&message, R { _vars: vars }).as_ref().to_string()
-        regex!(r"\$(\$|[A-Za-z0-9_-]+)").replace_all(&message, |s: &regex::Captures<'_>| {
-            let s = s.get(0).unwrap().as_str();
-            if s == "$$" {
-                "$"
-            } else {
-                let v = vars.get(&s.to_string().replace("$", ""));
-                if let Some(v) = v { v } else { "undefined" }
-            }
-        }).as_ref().to_string()
+/// An error that occurred while loading message resources.
+/// See `MessageLocator::try_load`.
+#[derive(Debug)]
+pub enum MessageLoadError {
+    /// A file could not be read.
+    Io { path: String, source: std::io::Error },
+    /// An HTTP request failed or returned an unsuccessful status.
+    Http { path: String, source: reqwest::Error },
+    /// A resource could not be parsed.
+    Parse { path: String, source: Box<dyn std::error::Error + Send + Sync> },
+    /// The requested locale is not one of the supported locales.
+    Unsupported { locale: String },
+    /// A browser `fetch` request failed or returned an unsuccessful status.
+    Fetch { path: String, message: String },
+}
+
+impl MessageLoadError {
+    /// Returns the path of the resource that failed to load, if any.
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            MessageLoadError::Io { path, .. } |
+            MessageLoadError::Http { path, .. } |
+            MessageLoadError::Parse { path, .. } => Some(path),
+            MessageLoadError::Fetch { path, .. } => Some(path),
+            MessageLoadError::Unsupported { .. } => None,
+        }
     }
+}
 
-    fn resolve_id(&self, root: Option<&serde_json::Value>, id: &Vec<String>) -> Option<String> {
-        let mut r = root;
-        for frag in id.iter() {
-            if r.is_none() {
-                return None;
-            }
-            r = r.unwrap().get(frag);
+impl std::fmt::Display for MessageLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MessageLoadError::Io { path, source } => write!(f, "Failed to load resource at {}: {}", path, source),
+            MessageLoadError::Http { path, source } => write!(f, "Failed to load resource at {}: {}", path, source),
+            MessageLoadError::Parse { path, source } => write!(f, "Failed to parse resource at {}: {}", path, source),
+            MessageLoadError::Unsupported { locale } => write!(f, "Unsupported locale {}", locale),
+            MessageLoadError::Fetch { path, message } => write!(f, "Failed to load resource at {}: {}", path, message),
         }
-        if r.is_none() {
-            return None;
+    }
+}
+
+impl std::error::Error for MessageLoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MessageLoadError::Io { source, .. } => Some(source),
+            MessageLoadError::Http { source, .. } => Some(source),
+            MessageLoadError::Parse { source, .. } => Some(source.as_ref()),
+            MessageLoadError::Unsupported { .. } => None,
+            MessageLoadError::Fetch { .. } => None,
         }
-        let r = r.unwrap().as_str();
-        if let Some(r) = r { Some(r.to_string()) } else { None }
     }
 }
 
-impl Clone for MessageLocator {
-    /// Clones the locator, sharing the same
-    /// resources.
-    fn clone(&self) -> Self {
-        Self {
-            _current_locale: self._current_locale.clone(),
-            _locale_path_components: self._locale_path_components.clone(),
-            _supported_locales: self._supported_locales.clone(),
-            _default_locale: self._default_locale.clone(),
-            _fallbacks: self._fallbacks.clone(),
-            _assets: self._assets.clone(),
-            _assets_src: self._assets_src.clone(),
-            _assets_base_file_names: self._assets_base_file_names.clone(),
-            _assets_clean_unused: self._assets_clean_unused,
-            _assets_load_via: self._assets_load_via,
+/// An invalid setting found when constructing a `MessageLocator`.
+/// See `MessageLocatorOptions::build`.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// A locale code does not parse. `setting` names the option
+    /// that holds it, such as `supported_locales`.
+    InvalidLocale { setting: &'static str, locale: String, reason: String },
+    /// The name or value of a header given to
+    /// `MessageLocatorAssetOptions::http_headers` is invalid.
+    InvalidHttpHeader { name: String },
+    /// Fallback locales that are not supported locales,
+    /// as pairs of locale and fallback, sorted.
+    UnsupportedFallbacks { entries: Vec<(String, String)> },
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::InvalidLocale { setting, locale, reason } => write!(f, "Invalid locale {} in {}: {}", locale, setting, reason),
+            ConfigError::InvalidHttpHeader { name } => write!(f, "Invalid HTTP header {}", name),
+            ConfigError::UnsupportedFallbacks { entries } => {
+                let entries: Vec<String> = entries.iter().map(|(locale, fallback)| format!("{} -> {}", locale, fallback)).collect();
+                write!(f, "Fallback locales are not supported locales: {}", entries.join(", "))
+            },
         }
     }
 }
 
+impl std::error::Error for ConfigError {}
+
+/// An issue found by `MessageLocator::preflight`.
+#[derive(Clone, PartialEq)]
+pub struct PreflightIssue {
+    /// Path of the asset file.
+    pub path: String,
+    /// Locale the file belongs to, or `None` for shared base files.
+    pub locale: Option<Locale>,
+    pub problem: PreflightProblem,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum PreflightProblem {
+    /// The file does not exist or could not be read.
+    Missing,
+    InvalidUtf8,
+    Empty,
+    /// The file could not be parsed; holds the parser's error.
+    Malformed(String),
+    /// The file is valid JSON, but not an object.
+    NotAnObject,
+    /// The assets are loaded over HTTP or through an `AssetLoader`,
+    /// which `preflight` does not check.
+    Unsupported,
+}
+
+/// Provides messages that are missing from every loaded locale,
+/// such as through cached machine translation or a remote service.
+/// See `MessageLocator::get_async`.
+///
+/// With the `sync` feature, implementations use `#[async_trait]`
+/// instead of `#[async_trait(?Send)]`, so that their futures are `Send`.
+#[cfg_attr(not(feature = "sync"), async_trait(?Send))]
+#[cfg_attr(feature = "sync", async_trait)]
+pub trait FallbackProvider: MaybeSendSync {
+    /// Returns a message for `id` in `locale`. `source` is the message
+    /// in the default locale, if it is loaded and defines it.
+    async fn translate(&self, locale: &Locale, id: &str, source: Option<&str>) -> Option<String>;
+}
+
+/// Loads base files from a custom source, such as a database, object storage
+/// or an embedded key-value store. See `MessageLocatorAssetOptions::loader`.
+///
+/// As with `FallbackProvider`, implementations use `#[async_trait]`
+/// with the `sync` feature and `#[async_trait(?Send)]` without it.
+#[cfg_attr(not(feature = "sync"), async_trait(?Send))]
+#[cfg_attr(feature = "sync", async_trait)]
+pub trait AssetLoader: MaybeSendSync {
+    /// Returns the parsed contents of the base file `base_name` in the
+    /// locale directory `locale_path`, which is empty for shared base files.
+    /// A missing file should be reported as an error, as other loaders do.
+    async fn load(&self, locale_path: &str, base_name: &str) -> Result<serde_json::Value, MessageLoadError>;
+}
+
+#[cfg(not(feature = "sync"))]
+type LocaleChangeListener = Shared<dyn Fn(&Locale)>;
+#[cfg(feature = "sync")]
+type LocaleChangeListener = Shared<dyn Fn(&Locale) + Send + Sync>;
+
+type ProvidedMessages = HashMap<(Locale, String), Option<String>>;
+
+/// The assets of a locale, with the base file defining each message
+/// if `MessageLocatorOptions::track_key_sources` is enabled.
+type LocaleAssets = (serde_json::Value, HashMap<String, String>);
+
+#[cfg(not(feature = "sync"))]
+type MissingHandler = Shared<dyn Fn(&str) -> String>;
+#[cfg(feature = "sync")]
+type MissingHandler = Shared<dyn Fn(&str) -> String + Send + Sync>;
+
+#[cfg(not(feature = "sync"))]
+type ResponseTransform = Shared<dyn Fn(serde_json::Value) -> serde_json::Value>;
+#[cfg(feature = "sync")]
+type ResponseTransform = Shared<dyn Fn(serde_json::Value) -> serde_json::Value + Send + Sync>;
+
+/// A function applied to a placeholder value through the `$name|function:argument` syntax.
+/// It receives the value and the argument, if any.
+#[cfg(not(feature = "sync"))]
+pub type MessageFormatter = Shared<dyn Fn(&str, Option<&str>) -> String>;
+/// A function applied to a placeholder value through the `$name|function:argument` syntax.
+/// It receives the value and the argument, if any.
+#[cfg(feature = "sync")]
+pub type MessageFormatter = Shared<dyn Fn(&str, Option<&str>) -> String + Send + Sync>;
+
 pub trait MessageLocatorFormatArgument {
     fn as_str(&self) -> Option<&'static str> { None }
     fn as_string(&self) -> Option<String> { None }
     fn as_string_map(&self) -> Option<HashMap<String, String>> { None }
+    /// Returns a variable name and its value formatted for the given locale,
+    /// which is the current locale of the `MessageLocator`.
+    fn as_localized_var(&self, _locale: &Locale) -> Option<(String, String)> { None }
+    /// Returns a positional value formatted for the given locale, which is
+    /// the current locale of the `MessageLocator`. Unlike `as_string`,
+    /// it does not select a contextual variant of the message.
+    fn as_localized_string(&self, _locale: &Locale) -> Option<String> { None }
 }
 
 impl MessageLocatorFormatArgument for &'static str {
@@ -351,7 +2500,21 @@ pub struct MessageLocatorOptions {
     _default_locale: RefCell<String>,
     _supported_locales: RefCell<Vec<String>>,
     _fallbacks: RefCell<HashMap<String, Vec<String>>>,
+    _aliases: RefCell<HashMap<String, String>>,
     _assets: RefCell<MessageLocatorAssetOptions>,
+    _case_insensitive_keys: Cell<bool>,
+    _implicit_language_fallback: Cell<bool>,
+    _placeholder_style: Cell<PlaceholderStyle>,
+    _max_format_steps: Cell<usize>,
+    _bidi_isolation: Cell<bool>,
+    _recursive_interpolation: Cell<bool>,
+    _id_separator: Cell<char>,
+    _declension_tables: RefCell<String>,
+    _track_key_sources: Cell<bool>,
+    _formatters: RefCell<HashMap<String, MessageFormatter>>,
+    _falsy_values: RefCell<HashSet<String>>,
+    _fallback_provider: RefCell<Option<Arc<dyn FallbackProvider>>>,
+    _on_missing: RefCell<Option<MissingHandler>>,
 }
 
 impl MessageLocatorOptions {
@@ -360,7 +2523,23 @@ impl MessageLocatorOptions {
             _default_locale: RefCell::new("en".to_string()),
             _supported_locales: RefCell::new(vec!["en".to_string()]),
             _fallbacks: RefCell::new(hashmap! {}),
+            _aliases: RefCell::new(hashmap! {}),
             _assets: RefCell::new(MessageLocatorAssetOptions::new()),
+            _case_insensitive_keys: Cell::new(false),
+            _implicit_language_fallback: Cell::new(false),
+            _placeholder_style: Cell::new(PlaceholderStyle::Dollar),
+            _max_format_steps: Cell::new(1000),
+            _bidi_isolation: Cell::new(false),
+            _recursive_interpolation: Cell::new(false),
+            _id_separator: Cell::new('.'),
+            _declension_tables: RefCell::new(String::from("declensions")),
+            _track_key_sources: Cell::new(false),
+            _formatters: RefCell::new(hashmap! {}),
+            _falsy_values: RefCell::new(hashset! {
+                String::from(""), String::from("0"), String::from("false"), String::from("no"),
+            }),
+            _fallback_provider: RefCell::new(None),
+            _on_missing: RefCell::new(None),
         }
     }
 
@@ -374,6 +2553,9 @@ impl MessageLocatorOptions {
         self
     }
 
+    /// Maps locales to their fallback locales, searched in order when
+    /// a message is missing. Every fallback locale must be one of the
+    /// supported locales.
     pub fn fallbacks<S: ToString>(&self, map: HashMap<S, Vec<S>>) -> &Self {
         self._fallbacks.replace(map.iter().map(|(k, v)| (
             k.to_string(),
@@ -382,10 +2564,193 @@ impl MessageLocatorOptions {
         self
     }
 
+    /// Adds the fallbacks of a single locale, replacing any previously
+    /// given for it, so that fallbacks can be chained:
+    /// `.fallback("fr", vec!["en"]).fallback("de", vec!["en"])`.
+    pub fn fallback<S: ToString, T: ToString>(&self, locale: S, targets: Vec<T>) -> &Self {
+        self._fallbacks.borrow_mut().insert(locale.to_string(), targets.iter().map(|s| s.to_string()).collect());
+        self
+    }
+
+    /// Maps locale codes, such as deprecated or legacy ones, to the
+    /// supported locales they stand for; for example, `zh-CN` to `zh-Hans`.
+    /// Aliases are matched ignoring case and resolved before checking support
+    /// in `supports_locale`, `match_supported`, `load`, `update_locale`
+    /// and `preload`, which then work with the canonical locale.
+    pub fn aliases<S: ToString, T: ToString>(&self, map: HashMap<S, T>) -> &Self {
+        self._aliases.replace(map.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect());
+        self
+    }
+
     pub fn assets(&self, options: &MessageLocatorAssetOptions) -> &Self {
         self._assets.replace(options.clone());
         self
     }
+
+    /// Indicates whether message keys are matched case-insensitively.
+    /// When enabled, keys are lowercased at load time and identifiers are
+    /// lowercased before lookup; as a consequence, keys that differ only
+    /// in case overwrite each other. Default is `false`.
+    pub fn case_insensitive_keys(&self, value: bool) -> &Self {
+        self._case_insensitive_keys.set(value);
+        self
+    }
+
+    /// Indicates whether a regional locale, such as `pt-BR`, falls back to
+    /// its language locale, `pt`, after its configured fallbacks, without
+    /// listing it in `fallbacks`. The language locale must be supported,
+    /// or be an alias of a supported locale, which is then used instead;
+    /// it is loaded along with the regional locale. Default is `false`.
+    pub fn implicit_language_fallback(&self, value: bool) -> &Self {
+        self._implicit_language_fallback.set(value);
+        self
+    }
+
+    /// Specifies the syntax of variable placeholders in messages.
+    /// Default is `PlaceholderStyle::Dollar`.
+    pub fn placeholder_style(&self, value: PlaceholderStyle) -> &Self {
+        self._placeholder_style.set(value);
+        self
+    }
+
+    /// Specifies the base file holding the declension tables used by the
+    /// built-in `case` formatter. Default is `"declensions"`; the base file
+    /// must also be listed in `MessageLocatorAssetOptions::base_file_names`.
+    ///
+    /// The file maps each noun, in the form it is passed as a variable,
+    /// to its forms keyed by case name:
+    ///
+    /// ```json
+    /// { "файл": { "genitive": "файла", "dative": "файлу" } }
+    /// ```
+    ///
+    /// With the table above, `$noun|case:genitive` formats the variable `noun`
+    /// set to `файл` as `файла`. Nouns or cases missing from the table
+    /// are left as is. Tables follow the current locale's fallbacks.
+    pub fn declension_tables<S: ToString>(&self, base_name: S) -> &Self {
+        self._declension_tables.replace(base_name.to_string());
+        self
+    }
+
+    /// Specifies the maximum number of formatting steps spent on a single
+    /// message, where each placeholder substitution and each formatter
+    /// applied to it counts as one step, including those within values
+    /// substituted by `recursive_interpolation`. Once the limit is exceeded, the remaining
+    /// placeholders are left unformatted, so that a pathological message
+    /// returns a partially formatted result instead of stalling.
+    /// Default is 1000.
+    pub fn max_format_steps(&self, value: usize) -> &Self {
+        self._max_format_steps.set(value);
+        self
+    }
+
+    /// Indicates whether to wrap variable values substituted into messages
+    /// of right-to-left locales in Unicode isolate marks (U+2068 and U+2069),
+    /// so that left-to-right values, such as user names and numbers, do not
+    /// reorder the surrounding text. Messages of left-to-right locales are
+    /// unaffected, unless a right-to-left direction override is set.
+    /// Default is `false`.
+    pub fn bidi_isolation(&self, value: bool) -> &Self {
+        self._bidi_isolation.set(value);
+        self
+    }
+
+    /// Specifies the character separating the segments of message identifiers,
+    /// such as `:` for keys that contain dots, as in `versions:1.2:notes`.
+    /// Identifiers returned by `MessageLocator`, such as by `message_keys`
+    /// and `find`, use it as well. Default is `.`.
+    pub fn id_separator(&self, value: char) -> &Self {
+        self._id_separator.set(value);
+        self
+    }
+
+    /// Indicates whether placeholders within variable values are substituted
+    /// as well, so that a message can be composed of fragments that
+    /// have placeholders of their own. Substitution nests at most 8 levels
+    /// deep, which stops variables that refer to each other.
+    /// Default is `false`.
+    pub fn recursive_interpolation(&self, value: bool) -> &Self {
+        self._recursive_interpolation.set(value);
+        self
+    }
+
+    /// Indicates whether to record which base file provided each message,
+    /// as reported by `MessageLocator::key_source`. Default is `false`,
+    /// as tracking adds overhead to loading.
+    pub fn track_key_sources(&self, value: bool) -> &Self {
+        self._track_key_sources.set(value);
+        self
+    }
+
+    /// Registers a formatter function usable from messages as
+    /// `$name|function` or `$name|function:argument`, overriding any
+    /// built-in formatter of the same name.
+    ///
+    /// The built-in formatters are:
+    ///
+    /// - `upper` and `lower`, which change the value's case.
+    /// - `currency`, which formats a numeric value with two decimal places
+    ///   in the conventions of the locale, followed by the currency code given
+    ///   as argument, such as `12,50 BRL` in Portuguese (`$price|currency:BRL`).
+    /// - `case`, which declines a noun into the grammatical case given as argument
+    ///   (`$noun|case:genitive`) using the declension tables of the current locale.
+    ///   See `MessageLocatorOptions::declension_tables`.
+    /// - `percent` and `permille`, which format a ratio as a percentage or
+    ///   per mille in the conventions of the locale, such as `50%` in English
+    ///   and `50 %` in French for `0.5`. The argument gives the count of
+    ///   fraction digits, by default zero (`$ratio|percent:1`).
+    /// - `range`, which formats a number range, as in `1-2`, or a single number
+    ///   in the conventions of the locale, joining the ends with an en dash,
+    ///   such as `1,000–2,000` in English (`$count|range`).
+    /// - `format`, with the `datetime` feature, which formats an ISO 8601 date
+    ///   or date and time, such as `2021-03-09T14:30`, in the conventions of
+    ///   the locale, as `DateTimeArg` does. The argument `date` or `time`
+    ///   selects only that part (`$starts|format:date`).
+    ///
+    /// Arguments consist of letters, digits, `_` and `-`. Any other character,
+    /// including `|`, `:`, `.` and whitespace, must be escaped with a backslash.
+    /// After a bare `$name`, a pipe that does not start a registered or built-in
+    /// formatter is kept as text, so `$name|word` formats as the value followed by `|word`.
+    pub fn formatter<S: ToString, F: Fn(&str, Option<&str>) -> String + MaybeSendSync + 'static>(&self, name: S, function: F) -> &Self {
+        self._formatters.borrow_mut().insert(name.to_string(), Shared::new(function));
+        self
+    }
+
+    /// Specifies the values that count as false in conditional contexts,
+    /// compared case-insensitively after trimming. See `MessageLocator::is_truthy`.
+    /// Default is the empty string, `0`, `false` and `no`.
+    pub fn falsy_values(&self, values: HashSet<String>) -> &Self {
+        self._falsy_values.replace(values);
+        self
+    }
+
+    /// Specifies a provider consulted by `MessageLocator::get_async` for
+    /// messages missing from every loaded locale. This is optional; note that
+    /// the first lookup of each missing message waits on the provider,
+    /// which may involve network latency.
+    pub fn fallback_provider(&self, provider: Arc<dyn FallbackProvider>) -> &Self {
+        self._fallback_provider.replace(Some(provider));
+        self
+    }
+
+    /// Specifies a function that produces the result of a missing message
+    /// from its identifier, such as a marker like `⟪id⟫` that makes untranslated
+    /// text stand out during QA, or a function that logs the identifier.
+    /// It is used by `MessageLocator::get`, `get_formatted`, `get_plural`,
+    /// `get_formatted_with_fallbacks` and `get_async`. By default, the identifier
+    /// is returned.
+    pub fn on_missing<F: Fn(&str) -> String + MaybeSendSync + 'static>(&self, handler: F) -> &Self {
+        self._on_missing.replace(Some(Shared::new(handler)));
+        self
+    }
+
+    /// Constructs a `MessageLocator` from these options like `MessageLocator::new`,
+    /// returning an error instead of panicking if a locale code
+    /// or an HTTP header is invalid, or if a fallback locale is not
+    /// one of the supported locales.
+    pub fn build(&self) -> Result<MessageLocator, ConfigError> {
+        MessageLocator::from_options(self)
+    }
 }
 
 pub struct MessageLocatorAssetOptions {
@@ -393,6 +2758,26 @@ pub struct MessageLocatorAssetOptions {
     _base_file_names: RefCell<Vec<String>>,
     _clean_unused: Cell<bool>,
     _load_via: Cell<MessageLocatorLoadVia>,
+    _memory: RefCell<HashMap<String, serde_json::Value>>,
+    _static_assets: RefCell<HashMap<(String, String), &'static str>>,
+    _loader: RefCell<Option<Arc<dyn AssetLoader>>>,
+    _shared_base_file_names: RefCell<Vec<String>>,
+    _shared_src: RefCell<String>,
+    _format: Cell<AssetFormat>,
+    _response_transform: RefCell<Option<ResponseTransform>>,
+    _http_timeout: Cell<Option<std::time::Duration>>,
+    _http_headers: RefCell<HashMap<String, String>>,
+    _http_retries: Cell<u32>,
+    _http_retry_backoff: Cell<std::time::Duration>,
+    _http_cache_dir: RefCell<Option<std::path::PathBuf>>,
+    _reload_interval: Cell<Option<std::time::Duration>>,
+    _inherit_language_files: Cell<bool>,
+    _front_matter: Cell<bool>,
+    _merge_strategy: Cell<MergeStrategy>,
+    _single_file: Cell<bool>,
+    _compression: Cell<Compression>,
+    _path_overrides: RefCell<HashMap<String, String>>,
+    _file_name_overrides: RefCell<HashMap<(String, String), String>>,
 }
 
 impl Clone for MessageLocatorAssetOptions {
@@ -402,6 +2787,26 @@ impl Clone for MessageLocatorAssetOptions {
             _base_file_names: self._base_file_names.clone(),
             _clean_unused: self._clean_unused.clone(),
             _load_via: self._load_via.clone(),
+            _memory: self._memory.clone(),
+            _static_assets: self._static_assets.clone(),
+            _loader: self._loader.clone(),
+            _shared_base_file_names: self._shared_base_file_names.clone(),
+            _shared_src: self._shared_src.clone(),
+            _format: self._format.clone(),
+            _response_transform: self._response_transform.clone(),
+            _http_timeout: self._http_timeout.clone(),
+            _http_headers: self._http_headers.clone(),
+            _http_retries: self._http_retries.clone(),
+            _http_retry_backoff: self._http_retry_backoff.clone(),
+            _http_cache_dir: self._http_cache_dir.clone(),
+            _reload_interval: self._reload_interval.clone(),
+            _inherit_language_files: self._inherit_language_files.clone(),
+            _front_matter: self._front_matter.clone(),
+            _merge_strategy: self._merge_strategy.clone(),
+            _single_file: self._single_file.clone(),
+            _compression: self._compression.clone(),
+            _path_overrides: self._path_overrides.clone(),
+            _file_name_overrides: self._file_name_overrides.clone(),
         }
     }
 }
@@ -413,6 +2818,26 @@ impl MessageLocatorAssetOptions {
             _base_file_names: RefCell::new(vec![]),
             _clean_unused: Cell::new(true),
             _load_via: Cell::new(MessageLocatorLoadVia::Http),
+            _memory: RefCell::new(HashMap::new()),
+            _static_assets: RefCell::new(HashMap::new()),
+            _loader: RefCell::new(None),
+            _shared_base_file_names: RefCell::new(vec![]),
+            _shared_src: RefCell::new("res/lang/shared".to_string()),
+            _format: Cell::new(AssetFormat::Json),
+            _response_transform: RefCell::new(None),
+            _http_timeout: Cell::new(None),
+            _http_headers: RefCell::new(HashMap::new()),
+            _http_retries: Cell::new(0),
+            _http_retry_backoff: Cell::new(std::time::Duration::from_millis(200)),
+            _http_cache_dir: RefCell::new(None),
+            _reload_interval: Cell::new(None),
+            _inherit_language_files: Cell::new(false),
+            _front_matter: Cell::new(false),
+            _merge_strategy: Cell::new(MergeStrategy::Overwrite),
+            _single_file: Cell::new(false),
+            _compression: Cell::new(Compression::None),
+            _path_overrides: RefCell::new(HashMap::new()),
+            _file_name_overrides: RefCell::new(HashMap::new()),
         }
     }
     
@@ -435,10 +2860,320 @@ impl MessageLocatorAssetOptions {
         self._load_via.set(value);
         self
     }
+
+    /// Specifies the assets loaded via `MessageLocatorLoadVia::Memory`,
+    /// keyed by `"{locale_path_component}/{base_file_name}"`, such as
+    /// `"en-US/common"`. Shared base files are keyed by their base file name alone.
+    pub fn memory(&self, map: HashMap<String, serde_json::Value>) -> &Self {
+        self._memory.replace(map);
+        self
+    }
+
+    /// Specifies the assets loaded via `MessageLocatorLoadVia::Static`,
+    /// keyed by locale path component and base file name, such as
+    /// `("en-US", "common")`, with the file contents as values. Shared base
+    /// files are keyed by an empty locale path component. The contents are
+    /// parsed in the asset format, so they can come from `include_str!`:
+    ///
+    /// ```ignore
+    /// .static_assets(hashmap! {
+    ///     ("en-US".to_string(), "common".to_string()) => include_str!("../res/lang/en-US/common.json"),
+    /// })
+    /// ```
+    pub fn static_assets(&self, map: HashMap<(String, String), &'static str>) -> &Self {
+        self._static_assets.replace(map);
+        self
+    }
+
+    /// Specifies a loader that supplies every base file, such as from
+    /// a database or object storage, in place of the one selected by `load_via`.
+    /// Shared base files are requested with an empty locale path component.
+    /// As the loader returns parsed values, `format` and `front_matter`
+    /// do not apply to its files.
+    pub fn loader(&self, loader: Arc<dyn AssetLoader>) -> &Self {
+        self._loader.replace(Some(loader));
+        self
+    }
+
+    /// Specifies base files that are shared by every locale, loaded from
+    /// `{src}/{base_name}.json` and merged into each locale's assets before
+    /// the locale's own base files, which may therefore override them.
+    pub fn shared_base_files<S: ToString, T: ToString>(&self, list: Vec<S>, src: T) -> &Self {
+        self._shared_base_file_names.replace(list.iter().map(|name| name.to_string()).collect());
+        self._shared_src.replace(src.to_string());
+        self
+    }
+
+    /// Specifies a function applied to the JSON of each HTTP response
+    /// before it is merged into the assets, such as for unwrapping
+    /// an envelope like `{ "data": { ... }, "meta": { ... } }`.
+    /// It only applies when loading via `MessageLocatorLoadVia::Http` or `Fetch`.
+    #[cfg(not(feature = "sync"))]
+    pub fn response_transform(&self, transform: Box<dyn Fn(serde_json::Value) -> serde_json::Value>) -> &Self {
+        self._response_transform.replace(Some(Shared::from(transform)));
+        self
+    }
+
+    /// Specifies a function applied to the JSON of each HTTP response
+    /// before it is merged into the assets, such as for unwrapping
+    /// an envelope like `{ "data": { ... }, "meta": { ... } }`.
+    /// It only applies when loading via `MessageLocatorLoadVia::Http` or `Fetch`.
+    ///
+    /// With the `sync` feature, the function must be `Send + Sync`.
+    #[cfg(feature = "sync")]
+    pub fn response_transform(&self, transform: Box<dyn Fn(serde_json::Value) -> serde_json::Value + Send + Sync>) -> &Self {
+        self._response_transform.replace(Some(Shared::from(transform)));
+        self
+    }
+
+    /// Specifies the time limit of each request when loading via
+    /// `MessageLocatorLoadVia::Http`, after which loading fails with
+    /// `MessageLoadError::Http`. Default is no limit.
+    ///
+    /// This is ignored on `wasm32` targets, where the browser sets the limit.
+    pub fn http_timeout(&self, value: std::time::Duration) -> &Self {
+        self._http_timeout.set(Some(value));
+        self
+    }
+
+    /// Specifies headers sent with each request when loading via
+    /// `MessageLocatorLoadVia::Http`, such as an `Authorization` header
+    /// for a private translation host. `MessageLocator::new` panics
+    /// and `MessageLocatorOptions::build` fails if a header name or value is invalid.
+    pub fn http_headers(&self, map: HashMap<String, String>) -> &Self {
+        self._http_headers.replace(map);
+        self
+    }
+
+    /// Specifies how many times a request is retried when loading via
+    /// `MessageLocatorLoadVia::Http` fails with a network error or
+    /// a server error status (5xx). Other statuses, such as 404,
+    /// fail immediately. Default is 0.
+    pub fn http_retries(&self, value: u32) -> &Self {
+        self._http_retries.set(value);
+        self
+    }
+
+    /// Specifies the delay before the first retry of a request;
+    /// each further retry waits twice as long as the previous one.
+    /// See `http_retries`. Default is 200 milliseconds.
+    /// On `wasm32` targets, retries are delayed only with the `wasm` feature.
+    pub fn http_retry_backoff(&self, value: std::time::Duration) -> &Self {
+        self._http_retry_backoff.set(value);
+        self
+    }
+
+    /// Specifies a directory where resources loaded via
+    /// `MessageLocatorLoadVia::Http` are saved after each successful request.
+    /// When a later request fails, such as when the device is offline,
+    /// the saved copy is used instead and a warning is logged.
+    /// The most recent copy always replaces the previous one.
+    pub fn http_cache_dir(&self, dir: std::path::PathBuf) -> &Self {
+        self._http_cache_dir.replace(Some(dir));
+        self
+    }
+
+    /// Specifies how often `SharedMessageLocator::spawn_auto_reload`
+    /// reloads the assets of the current locale and its fallbacks,
+    /// such as to pick up translations published to an HTTP host
+    /// by a long-running server. Default is no automatic reload.
+    pub fn reload_interval(&self, value: std::time::Duration) -> &Self {
+        self._reload_interval.set(Some(value));
+        self
+    }
+
+    /// Specifies whether a regional locale inherits the files of its language
+    /// directory. When enabled, loading `en-GB` reads each base file from
+    /// `{src}/en` first and merges `{src}/en-GB` over it key by key,
+    /// so regional files only need to contain the messages that differ.
+    /// Both files must exist. Default is `false`.
+    ///
+    /// This is independent of locale fallbacks, which are searched per message.
+    pub fn inherit_language_files(&self, value: bool) -> &Self {
+        self._inherit_language_files.set(value);
+        self
+    }
+
+    /// Indicates whether JSON asset files may begin with a YAML front-matter
+    /// block of metadata, delimited by `---` lines:
+    ///
+    /// ```text
+    /// ---
+    /// title: Getting started
+    /// order: 1
+    /// ---
+    /// { "intro": "Welcome!" }
+    /// ```
+    ///
+    /// The metadata is stored under the `_meta` key of the file's namespace,
+    /// as returned by `MessageLocator::metadata` or `get_value("intro_page._meta")`
+    /// for a base file named `intro_page`, and the rest of the file is loaded
+    /// as messages. Files without front matter load as usual. Default is `false`.
+    pub fn front_matter(&self, value: bool) -> &Self {
+        self._front_matter.set(value);
+        self
+    }
+
+    /// Specifies how a base file is combined with a previously loaded one
+    /// of the same namespace, such as when several base file names
+    /// map into it. Shared base files load before the others.
+    /// Default is `MergeStrategy::Overwrite`.
+    pub fn merge_strategy(&self, value: MergeStrategy) -> &Self {
+        self._merge_strategy.set(value);
+        self
+    }
+
+    /// Specifies whether each locale is loaded from a single file at
+    /// `{src}/{locale}.json` (or `.yaml`), whose top-level object holds
+    /// every message, instead of a directory of base files. Base file names
+    /// and shared base files are then ignored, and identifiers start at the
+    /// file's top-level keys. In-memory and static assets are keyed by the locale
+    /// alone, and an `AssetLoader` is called with an empty base file name.
+    /// Not supported by `AssetFormat::Bundle`. Default is `false`.
+    pub fn single_file(&self, value: bool) -> &Self {
+        self._single_file.set(value);
+        self
+    }
+
+    /// Specifies the compression of asset files loaded via
+    /// `MessageLocatorLoadVia::FileSystem` or `Http`. With `Compression::Gzip`,
+    /// files are named with an additional `.gz` extension, as in `_.json.gz`,
+    /// and decompressed after loading; HTTP requests also accept gzip
+    /// content encoding. Default is `Compression::None`.
+    pub fn compression(&self, value: Compression) -> &Self {
+        self._compression.set(value);
+        self
+    }
+
+    /// Specifies the format of the assets. Default is `AssetFormat::Json`.
+    pub fn format(&self, value: AssetFormat) -> &Self {
+        self._format.set(value);
+        self
+    }
+
+    /// Overrides the directory name of a locale, such as `en_us` or `english`
+    /// for `en-US`, for asset layouts whose folder names are not locale tags.
+    /// By default, a locale's directory is named as the locale code given to
+    /// `MessageLocatorOptions::supported_locales`.
+    pub fn path_for<S: ToString, D: ToString>(&self, locale: S, dir_name: D) -> &Self {
+        self._path_overrides.borrow_mut().insert(locale.to_string(), dir_name.to_string());
+        self
+    }
+
+    /// Overrides the file name of a base file for a locale, such as
+    /// `messages_en` for the base file `_` of `en`, for asset layouts whose
+    /// file names differ per locale. The messages are still found under
+    /// the base file name, as in `_.message_id`. The file name excludes
+    /// the extension and, for `MessageLocatorLoadVia::Memory` and
+    /// `MessageLocatorLoadVia::Static`, replaces the base file name in the key.
+    pub fn file_name_for<S: ToString, B: ToString, F: ToString>(&self, locale: S, base_name: B, file_name: F) -> &Self {
+        self._file_name_overrides.borrow_mut().insert((locale.to_string(), base_name.to_string()), file_name.to_string());
+        self
+    }
 }
 
 #[derive(Copy, Clone)]
 pub enum MessageLocatorLoadVia {
     FileSystem,
     Http,
+    /// Loads JSON assets from the map given to `MessageLocatorAssetOptions::memory`,
+    /// without any IO. Not supported by `AssetFormat::Bundle`.
+    Memory,
+    /// Parses assets from the strings given to `MessageLocatorAssetOptions::static_assets`,
+    /// such as ones compiled into the binary, without any IO. Not supported by `AssetFormat::Bundle`.
+    Static,
+    /// Requests assets from the same URLs as `Http` with the `fetch` API
+    /// of the browser window, for `wasm32` web applications. Retries,
+    /// caching, timeouts and headers do not apply. Requires the `wasm` feature;
+    /// without it, loading fails with an `Unsupported` IO error.
+    Fetch,
+}
+
+impl MessageLocatorLoadVia {
+    /// Returns whether assets are requested over HTTP.
+    fn is_http(&self) -> bool {
+        matches!(self, MessageLocatorLoadVia::Http | MessageLocatorLoadVia::Fetch)
+    }
+}
+
+/// How base files sharing a namespace are combined.
+/// See `MessageLocatorAssetOptions::merge_strategy`.
+#[derive(Copy, Clone, PartialEq)]
+pub enum MergeStrategy {
+    /// The last file replaces the namespace.
+    Overwrite,
+    /// The first file is kept and later ones are ignored.
+    KeepExisting,
+    /// Objects are merged recursively; on conflicting keys,
+    /// the last file wins. This allows splitting a namespace across files.
+    DeepMerge,
+}
+
+/// The compression of asset files.
+/// See `MessageLocatorAssetOptions::compression`.
+#[derive(Copy, Clone, PartialEq)]
+pub enum Compression {
+    None,
+    /// Files compressed with gzip, named like `_.json.gz`.
+    Gzip,
+}
+
+impl Compression {
+    /// Decompresses the contents of an asset file. Gzip contents that
+    /// were already decoded, such as by HTTP content encoding, are returned as they are.
+    fn decompress(&self, content: Vec<u8>) -> std::io::Result<Vec<u8>> {
+        match self {
+            Compression::Gzip if content.starts_with(&[0x1f, 0x8b]) => {
+                let mut r = Vec::new();
+                std::io::Read::read_to_end(&mut flate2::read::GzDecoder::new(content.as_slice()), &mut r)?;
+                Ok(r)
+            },
+            _ => Ok(content),
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+pub enum AssetFormat {
+    /// JSON files at `{src}/{locale}/{base_name}.json`.
+    Json,
+    /// YAML files at `{src}/{locale}/{base_name}.yaml`.
+    Yaml,
+    /// Binary bundles at `{src}/{locale}.bundle`, as produced by
+    /// `MessageLocator::compile_bundle`. Base file names are ignored.
+    Bundle,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum PlaceholderStyle {
+    /// Placeholders like `$name` or `$name|upper`, with `$$` as a literal dollar sign.
+    /// A backslash also escapes a dollar sign, so `\$name` formats as `$name`;
+    /// `\\` formats as a single backslash.
+    /// The braced form `${name}` also accepts a default value used when
+    /// the variable is missing, as in `${name:Guest}`.
+    Dollar,
+    /// Placeholders like `{name}` or `{name|upper}`, with `{{` and `}}`
+    /// as literal braces. Dollar signs, as in prices, are left untouched.
+    /// A default value used when the variable is missing follows a colon,
+    /// as in `{name:Guest}`.
+    Braces,
+}
+
+impl AssetFormat {
+    /// Returns the file extension of the base files.
+    fn extension(&self) -> &'static str {
+        match self {
+            AssetFormat::Json => "json",
+            AssetFormat::Yaml => "yaml",
+            AssetFormat::Bundle => "bundle",
+        }
+    }
+
+    /// Parses a base file into a JSON value.
+    fn parse(&self, content: &[u8]) -> Result<serde_json::Value, Box<dyn std::error::Error + Send + Sync>> {
+        match self {
+            AssetFormat::Yaml => Ok(serde_yaml::from_slice(content)?),
+            _ => Ok(serde_json::from_slice(content)?),
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,318 @@
+use super::number_format::{self, NumberSymbols};
+use super::plural_rules;
+use std::collections::HashMap;
+
+/// Keywords accepted as the second part of an ICU-style argument, as in `{count, plural, ...}`.
+pub(crate) const KNOWN_KEYWORDS: [&str; 6] = ["plural", "select", "selectordinal", "number", "date", "time"];
+
+/// Checks that a message's ICU-style blocks are well-formed: braces are
+/// balanced and every `{argument, keyword, ...}` block uses a known keyword.
+pub(crate) fn validate_icu_syntax(message: &str) -> Result<(), String> {
+    let chars: Vec<char> = message.chars().collect();
+    let mut i = 0;
+    validate_text(&chars, &mut i, false)?;
+    Ok(())
+}
+
+fn validate_text(chars: &[char], i: &mut usize, nested: bool) -> Result<(), String> {
+    while *i < chars.len() {
+        match chars[*i] {
+            '{' => {
+                *i += 1;
+                validate_argument(chars, i)?;
+            },
+            '}' => {
+                if nested {
+                    return Ok(());
+                }
+                return Err(format!("Unexpected '}}' at position {}.", *i));
+            },
+            _ => { *i += 1; },
+        }
+    }
+    if nested {
+        return Err(String::from("Unbalanced braces: missing '}'."));
+    }
+    Ok(())
+}
+
+/// Validates an argument block, starting after its `{` and ending after its `}`.
+fn validate_argument(chars: &[char], i: &mut usize) -> Result<(), String> {
+    let name = read_until(chars, i, &[',', '}', '{']);
+    if *i >= chars.len() {
+        return Err(String::from("Unbalanced braces: missing '}'."));
+    }
+    if chars[*i] == '{' {
+        return Err(format!("Unexpected '{{' at position {}.", *i));
+    }
+    if chars[*i] == '}' {
+        *i += 1;
+        return Ok(());
+    }
+    if name.trim().is_empty() {
+        return Err(String::from("Missing argument name."));
+    }
+    *i += 1;
+    let keyword = read_until(chars, i, &[',', '}', '{']);
+    let keyword = keyword.trim();
+    if !KNOWN_KEYWORDS.contains(&keyword) {
+        return Err(format!("Unknown keyword '{}'.", keyword));
+    }
+    if *i >= chars.len() {
+        return Err(String::from("Unbalanced braces: missing '}'."));
+    }
+    if chars[*i] == '{' {
+        return Err(format!("Unexpected '{{' at position {}.", *i));
+    }
+    if chars[*i] == '}' {
+        *i += 1;
+        return Ok(());
+    }
+    *i += 1;
+    if keyword != "plural" && keyword != "select" && keyword != "selectordinal" {
+        read_until(chars, i, &['}', '{']);
+        if *i >= chars.len() {
+            return Err(String::from("Unbalanced braces: missing '}'."));
+        }
+        if chars[*i] == '{' {
+            return Err(format!("Unexpected '{{' at position {}.", *i));
+        }
+        *i += 1;
+        return Ok(());
+    }
+    loop {
+        let selector = read_until(chars, i, &['{', '}']);
+        if *i >= chars.len() {
+            return Err(String::from("Unbalanced braces: missing '}'."));
+        }
+        if chars[*i] == '}' {
+            if !selector.trim().is_empty() {
+                return Err(format!("Missing branch for '{}'.", selector.trim()));
+            }
+            *i += 1;
+            return Ok(());
+        }
+        if selector.trim().is_empty() {
+            return Err(format!("Missing selector at position {}.", *i));
+        }
+        *i += 1;
+        validate_text(chars, i, true)?;
+        *i += 1;
+    }
+}
+
+/// Replaces ICU-style select blocks, as in `{gender, select, male {He} other {They}}`,
+/// with the branch whose selector equals the value of the variable, or else
+/// the `other` branch, or else nothing.
+///
+/// Plural blocks, as in `{count, plural, =0 {No items} one {# item} other {# items}}`,
+/// are replaced with the branch whose `=N` selector equals the number, or else
+/// the branch of its plural category in `language`, or else the `other` branch.
+/// `#` within the chosen branch, outside nested plural blocks, is replaced
+/// with the number formatted for `language`, keeping the fraction digits it is
+/// written with. The number may also be a range, as in `1-2`, whose category is that
+/// of its end; see `plural_rules::plural_category_of`. Plural blocks whose variable
+/// is missing or not a number or range are left as they are.
+///
+/// Blocks within the chosen branch are replaced as well, to any depth.
+/// Other blocks are left as they are. If `escaped_braces` is `true`,
+/// `{{` and `}}` are literal braces and copied as they are.
+pub(crate) fn apply_selects(message: &str, vars: &HashMap<String, String>, escaped_braces: bool, language: &str) -> String {
+    let chars: Vec<char> = message.chars().collect();
+    let mut r = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if escaped_braces && (chars[i] == '{' || chars[i] == '}') && chars.get(i + 1) == Some(&chars[i]) {
+            r.push(chars[i]);
+            r.push(chars[i]);
+            i += 2;
+            continue;
+        }
+        if chars[i] == '{' {
+            if let Some((branch, end)) = read_select(&chars, i, vars, escaped_braces, language) {
+                r.push_str(&apply_selects(&branch, vars, escaped_braces, language));
+                i = end;
+                continue;
+            }
+        }
+        r.push(chars[i]);
+        i += 1;
+    }
+    r
+}
+
+/// Replaces ICU-style number blocks, as in `{count, number}`, `{ratio, number, percent}`
+/// or `{price, number, .2}`, with the value of the variable formatted with
+/// the given symbols: as is, as a percentage, or with a fixed count of
+/// fraction digits. Values that are not numbers are copied as they are.
+/// Blocks of missing variables or unknown styles are left as they are.
+/// `escaped_braces` is as in `apply_selects`.
+pub(crate) fn apply_numbers(message: &str, vars: &HashMap<String, String>, symbols: &NumberSymbols, escaped_braces: bool) -> String {
+    let chars: Vec<char> = message.chars().collect();
+    let mut r = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if escaped_braces && (chars[i] == '{' || chars[i] == '}') && chars.get(i + 1) == Some(&chars[i]) {
+            r.push(chars[i]);
+            r.push(chars[i]);
+            i += 2;
+            continue;
+        }
+        if chars[i] == '{' {
+            if let Some((number, end)) = read_number(&chars, i, vars, symbols) {
+                r.push_str(&number);
+                i = end;
+                continue;
+            }
+        }
+        r.push(chars[i]);
+        i += 1;
+    }
+    r
+}
+
+/// Reads a number block starting at the `{` at `start`, returning the
+/// formatted number and the position after the block, or `None` if the block
+/// is not a number block that can be formatted.
+fn read_number(chars: &[char], start: usize, vars: &HashMap<String, String>, symbols: &NumberSymbols) -> Option<(String, usize)> {
+    let mut i = start + 1;
+    let block = read_until(chars, &mut i, &['{', '}']);
+    if chars.get(i) != Some(&'}') {
+        return None;
+    }
+    let parts: Vec<&str> = block.split(',').map(|part| part.trim()).collect();
+    if parts.len() < 2 || parts.len() > 3 || parts[1] != "number" {
+        return None;
+    }
+    let value = vars.get(parts[0])?;
+    let n = match value.parse::<f64>() {
+        Ok(n) => n,
+        Err(_) => return Some((value.clone(), i + 1)),
+    };
+    let r = match parts.get(2) {
+        None => number_format::format_decimal(n, number_format::fraction_digits(n), symbols),
+        Some(&"percent") => number_format::format_scaled(n, 100.0, 0, "%", symbols),
+        Some(style) => number_format::format_decimal(n, style.strip_prefix('.')?.parse::<usize>().ok()?, symbols),
+    };
+    Some((r, i + 1))
+}
+
+/// Reads a select or plural block starting at the `{` at `start`, returning
+/// the chosen branch and the position after the block, or `None` if the block
+/// is not a well-formed select block or a plural block of a number.
+fn read_select(chars: &[char], start: usize, vars: &HashMap<String, String>, escaped_braces: bool, language: &str) -> Option<(String, usize)> {
+    let mut i = start + 1;
+    let name = read_until(chars, &mut i, &[',', '{', '}']);
+    if chars.get(i) != Some(&',') {
+        return None;
+    }
+    i += 1;
+    let keyword = read_until(chars, &mut i, &[',', '{', '}']);
+    let plural = match keyword.trim() {
+        "select" => false,
+        "plural" => true,
+        _ => return None,
+    };
+    if chars.get(i) != Some(&',') {
+        return None;
+    }
+    i += 1;
+    let value = vars.get(name.trim()).map(|v| v.as_str());
+    // the exact selector, the plural category and the formatted number of a plural block's number
+    let (exact, category, number) = if plural {
+        let value = value?.trim();
+        let category = plural_rules::plural_category_of(language, value)?.as_str();
+        let number = number_format::format_range(value, &number_format::number_symbols(language))?;
+        (value.parse::<f64>().ok().map(|n| format!("={}", n)), Some(category), Some(number))
+    } else {
+        (None, None, None)
+    };
+    let (mut chosen, mut categorized, mut other) = (None, None, None);
+    loop {
+        let selector = read_until(chars, &mut i, &['{', '}']);
+        let selector = selector.trim();
+        match chars.get(i) {
+            Some('}') if selector.is_empty() => break,
+            Some('{') if !selector.is_empty() => {},
+            _ => return None,
+        }
+        let end = matching_brace(chars, i)?;
+        let branch: String = chars[i + 1..end].iter().collect();
+        if chosen.is_none() && (Some(selector) == exact.as_deref() || (!plural && Some(selector) == value)) {
+            chosen = Some(branch);
+        } else if categorized.is_none() && Some(selector) == category {
+            categorized = Some(branch);
+        } else if selector == "other" {
+            other = Some(branch);
+        }
+        i = end + 1;
+    }
+    let branch = chosen.or(categorized).or(other).unwrap_or_default();
+    match number {
+        Some(number) => Some((replace_number_signs(&branch, &number, escaped_braces), i + 1)),
+        None => Some((branch, i + 1)),
+    }
+}
+
+/// Replaces `#` with `number`, except within nested plural blocks,
+/// whose `#` refers to their own number. `escaped_braces` is as in `apply_selects`.
+fn replace_number_signs(branch: &str, number: &str, escaped_braces: bool) -> String {
+    let chars: Vec<char> = branch.chars().collect();
+    let mut r = String::new();
+    // whether each enclosing brace opens a plural block
+    let mut plurals = Vec::<bool>::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let ch = chars[i];
+        if escaped_braces && (ch == '{' || ch == '}') && chars.get(i + 1) == Some(&ch) {
+            r.push(ch);
+            r.push(ch);
+            i += 2;
+            continue;
+        }
+        match ch {
+            '{' => {
+                let mut j = i + 1;
+                let header = read_until(&chars, &mut j, &['{', '}']);
+                plurals.push(header.split(',').nth(1).map(|keyword| keyword.trim()) == Some("plural"));
+            },
+            '}' => { plurals.pop(); },
+            '#' if !plurals.contains(&true) => {
+                r.push_str(number);
+                i += 1;
+                continue;
+            },
+            _ => {},
+        }
+        r.push(ch);
+        i += 1;
+    }
+    r
+}
+
+/// Returns the position of the `}` closing the `{` at `open`.
+fn matching_brace(chars: &[char], open: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, ch) in chars.iter().enumerate().skip(open) {
+        match ch {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            },
+            _ => {},
+        }
+    }
+    None
+}
+
+fn read_until(chars: &[char], i: &mut usize, stop: &[char]) -> String {
+    let mut r = String::new();
+    while *i < chars.len() && !stop.contains(&chars[*i]) {
+        r.push(chars[*i]);
+        *i += 1;
+    }
+    r
+}
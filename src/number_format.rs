@@ -0,0 +1,147 @@
+use super::{Locale, MessageLocatorFormatArgument};
+
+/// The CLDR number symbols of a language.
+pub(crate) struct NumberSymbols {
+    pub(crate) decimal: &'static str,
+    pub(crate) group: &'static str,
+    /// Whether the percent and permille signs precede the number, as in `%50`.
+    pub(crate) sign_first: bool,
+    /// The space between the number and the percent or permille sign.
+    pub(crate) sign_spacing: &'static str,
+}
+
+/// Returns the number symbols of a language subtag.
+/// Languages without known symbols use the English ones.
+pub(crate) fn number_symbols(language: &str) -> NumberSymbols {
+    let (decimal, group, sign_first, sign_spacing) = match language {
+        "fr" => (",", "\u{202F}", false, "\u{202F}"),
+        "de" | "es" | "da" => (",", ".", false, "\u{00A0}"),
+        "ru" | "uk" | "sv" | "nb" | "no" | "nn" | "fi" | "cs" | "sk" => (",", "\u{00A0}", false, "\u{00A0}"),
+        "pl" => (",", "\u{00A0}", false, ""),
+        "pt" | "it" | "nl" | "id" => (",", ".", false, ""),
+        "tr" => (",", ".", true, ""),
+        _ => (".", ",", false, ""),
+    };
+    NumberSymbols { decimal, group, sign_first, sign_spacing }
+}
+
+/// Formats a number with a fixed count of fraction digits,
+/// grouping the integer digits by thousands.
+pub(crate) fn format_decimal(value: f64, fraction_digits: usize, symbols: &NumberSymbols) -> String {
+    let digits = format!("{:.*}", fraction_digits, value.abs());
+    let (integer, fraction) = match digits.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (digits.as_str(), None),
+    };
+    let mut r = String::new();
+    if value < 0.0 && digits.chars().any(|ch| ch != '0' && ch != '.') {
+        r.push('-');
+    }
+    for (i, ch) in integer.chars().enumerate() {
+        let remaining = integer.len() - i;
+        if i != 0 && remaining % 3 == 0 {
+            r.push_str(symbols.group);
+        }
+        r.push(ch);
+    }
+    if let Some(fraction) = fraction {
+        r.push_str(symbols.decimal);
+        r.push_str(fraction);
+    }
+    r
+}
+
+/// Returns the count of fraction digits needed to show a number exactly.
+pub(crate) fn fraction_digits(value: f64) -> usize {
+    value.to_string().split_once('.').map_or(0, |(_, fraction)| fraction.len())
+}
+
+/// Returns the count of fraction digits written in a decimal string,
+/// such as 1 for `1.0`, or `None` if it is not a decimal number.
+pub(crate) fn visible_fraction_digits(value: &str) -> Option<usize> {
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    let is_digits = |s: &str| s.chars().all(|ch| ch.is_ascii_digit());
+    if integer.is_empty() || !is_digits(integer) || !is_digits(fraction) || (digits.contains('.') && fraction.is_empty()) {
+        return None;
+    }
+    Some(fraction.len())
+}
+
+/// Splits a number range, as in `1-2` or `1–2`, into its start and end.
+pub(crate) fn split_range(value: &str) -> Option<(&str, &str)> {
+    let separator = value.char_indices().skip(1).find(|&(_, ch)| ch == '-' || ch == '–')?;
+    let (start, end) = (value[..separator.0].trim(), value[separator.0 + separator.1.len_utf8()..].trim());
+    visible_fraction_digits(start)?;
+    visible_fraction_digits(end)?;
+    Some((start, end))
+}
+
+/// Formats a decimal string, keeping the fraction digits it is written with,
+/// so that `1.0` stays `1.0` rather than `1`.
+pub(crate) fn format_decimal_str(value: &str, symbols: &NumberSymbols) -> Option<String> {
+    let fraction_digits = visible_fraction_digits(value)?;
+    Some(format_decimal(value.parse::<f64>().ok()?, fraction_digits, symbols))
+}
+
+/// Formats a number range, as in `1-2`, or a single number,
+/// joining the ends of a range with an en dash.
+pub(crate) fn format_range(value: &str, symbols: &NumberSymbols) -> Option<String> {
+    match split_range(value) {
+        Some((start, end)) => Some(format!("{}–{}", format_decimal_str(start, symbols)?, format_decimal_str(end, symbols)?)),
+        None => format_decimal_str(value, symbols),
+    }
+}
+
+/// Formats a ratio scaled by `scale`, such as 100 for percent, followed
+/// or preceded by `sign` as the language places it.
+pub(crate) fn format_scaled(ratio: f64, scale: f64, fraction_digits: usize, sign: &str, symbols: &NumberSymbols) -> String {
+    let number = format_decimal(ratio * scale, fraction_digits, symbols);
+    if symbols.sign_first {
+        format!("{}{}{}", sign, symbols.sign_spacing, number)
+    } else {
+        format!("{}{}{}", number, symbols.sign_spacing, sign)
+    }
+}
+
+/// A number formatting argument that is grouped and punctuated by the
+/// conventions of the current locale, such as `1,000,000.5` in English
+/// and `1.000.000,5` in Portuguese. It is available to the message
+/// as a positional variable, like other numbers; see `NumberArg`
+/// for a named variable.
+///
+/// ```ignore
+/// msg_locator.get_formatted("cart.total", vec![&LocalizedNumber(1000000.0)]);
+/// ```
+pub struct LocalizedNumber(pub f64);
+
+impl MessageLocatorFormatArgument for LocalizedNumber {
+    fn as_localized_string(&self, locale: &Locale) -> Option<String> {
+        Some(format_decimal(self.0, fraction_digits(self.0), &number_symbols(&locale.language())))
+    }
+}
+
+/// A named number formatting argument, formatted as `LocalizedNumber` is.
+/// It is available to the message as the variable of the given name,
+/// so it can be combined with a map of other variables.
+///
+/// ```ignore
+/// let vars = localization_vars!{ "name" => "Ana" };
+/// msg_locator.get_formatted("cart.summary", vec![&vars, &NumberArg::new("total", 1000000.0)]);
+/// ```
+pub struct NumberArg {
+    name: String,
+    value: f64,
+}
+
+impl NumberArg {
+    pub fn new<S: ToString>(name: S, value: f64) -> Self {
+        Self { name: name.to_string(), value }
+    }
+}
+
+impl MessageLocatorFormatArgument for NumberArg {
+    fn as_localized_var(&self, locale: &Locale) -> Option<(String, String)> {
+        Some((self.name.clone(), LocalizedNumber(self.value).as_localized_string(locale)?))
+    }
+}
@@ -0,0 +1,176 @@
+use super::number_format;
+
+/// A CLDR plural category.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub(crate) enum PluralCategory {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+impl PluralCategory {
+    /// Returns the category name, used as the message key suffix.
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            PluralCategory::Zero => "zero",
+            PluralCategory::One => "one",
+            PluralCategory::Two => "two",
+            PluralCategory::Few => "few",
+            PluralCategory::Many => "many",
+            PluralCategory::Other => "other",
+        }
+    }
+}
+
+/// Selects the CLDR cardinal plural category of an integer count
+/// for a language subtag. Languages without known rules use `Other`.
+pub(crate) fn plural_category(language: &str, count: i64) -> PluralCategory {
+    use PluralCategory::*;
+    let n = count.unsigned_abs();
+    let n10 = n % 10;
+    let n100 = n % 100;
+    let n1000000 = n % 1_000_000;
+    match language {
+        "ja" | "zh" | "ko" | "vi" | "th" | "id" | "ms" | "lo" | "my" | "km" => Other,
+        "en" | "de" | "nl" | "sv" | "da" | "no" | "nb" | "nn" | "fi" | "et" | "el" | "hu"
+        | "tr" | "bg" | "eu" | "gl" | "af" | "sq" | "ka" | "kk" | "az" | "ur" | "sw" => {
+            if n == 1 { One } else { Other }
+        },
+        "es" | "it" | "ca" => {
+            if n == 1 { One } else if n != 0 && n1000000 == 0 { Many } else { Other }
+        },
+        "fr" | "pt" => {
+            if n <= 1 { One } else if n1000000 == 0 { Many } else { Other }
+        },
+        "hi" | "bn" | "fa" | "gu" | "kn" | "am" | "zu" => {
+            if n <= 1 { One } else { Other }
+        },
+        "ru" | "uk" | "be" => {
+            if n10 == 1 && n100 != 11 { One }
+            else if (2..=4).contains(&n10) && !(12..=14).contains(&n100) { Few }
+            else { Many }
+        },
+        "pl" => {
+            if n == 1 { One }
+            else if (2..=4).contains(&n10) && !(12..=14).contains(&n100) { Few }
+            else { Many }
+        },
+        "hr" | "sr" | "bs" => {
+            if n10 == 1 && n100 != 11 { One }
+            else if (2..=4).contains(&n10) && !(12..=14).contains(&n100) { Few }
+            else { Other }
+        },
+        "cs" | "sk" => {
+            if n == 1 { One } else if (2..=4).contains(&n) { Few } else { Other }
+        },
+        "lt" => {
+            if n10 == 1 && !(11..=19).contains(&n100) { One }
+            else if n10 >= 2 && !(11..=19).contains(&n100) { Few }
+            else { Other }
+        },
+        "lv" => {
+            if n10 == 0 || (11..=19).contains(&n100) { Zero }
+            else if n10 == 1 && n100 != 11 { One }
+            else { Other }
+        },
+        "ro" => {
+            if n == 1 { One } else if n == 0 || (2..=19).contains(&n100) { Few } else { Other }
+        },
+        "sl" => {
+            match n100 { 1 => One, 2 => Two, 3 | 4 => Few, _ => Other }
+        },
+        "he" => {
+            match n { 1 => One, 2 => Two, _ => Other }
+        },
+        "ar" => {
+            match n {
+                0 => Zero,
+                1 => One,
+                2 => Two,
+                _ if (3..=10).contains(&n100) => Few,
+                _ if n100 >= 11 => Many,
+                _ => Other,
+            }
+        },
+        "ga" => {
+            match n { 1 => One, 2 => Two, 3..=6 => Few, 7..=10 => Many, _ => Other }
+        },
+        "cy" => {
+            match n { 0 => Zero, 1 => One, 2 => Two, 3 => Few, 6 => Many, _ => Other }
+        },
+        _ => Other,
+    }
+}
+
+/// Selects the CLDR cardinal plural category of a number written as
+/// a string, such as `1`, `1.0` or `-2.50`, for a language subtag. Fraction
+/// digits that are written count, even if they are zero: in English, `1`
+/// is `One` but `1.0` is `Other`.
+///
+/// A range, as in `1-2` or `1–2`, selects the category of its end, as
+/// CLDR range rules do for most languages. Returns `None` if `number`
+/// is neither a decimal number nor a range of them.
+pub(crate) fn plural_category_of(language: &str, number: &str) -> Option<PluralCategory> {
+    let number = number.trim();
+    if let Some((_, end)) = number_format::split_range(number) {
+        return plural_category_of(language, end);
+    }
+    let v = number_format::visible_fraction_digits(number)?;
+    let digits = number.strip_prefix('-').unwrap_or(number);
+    let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    let i = integer.parse::<u64>().ok()?;
+    if v == 0 {
+        return Some(plural_category(language, i.min(i64::MAX as u64) as i64));
+    }
+    // only the lowest digits of the fraction are needed by the rules
+    let f = fraction[fraction.len().saturating_sub(18)..].parse::<u64>().ok()?;
+    Some(decimal_plural_category(language, i, v, f))
+}
+
+/// Selects the plural category of a number with `v` visible fraction
+/// digits, more than zero, whose integer digits are `i` and fraction
+/// digits are `f`, following the CLDR operands of the same names.
+fn decimal_plural_category(language: &str, i: u64, v: usize, f: u64) -> PluralCategory {
+    use PluralCategory::*;
+    let integer = i.min(i64::MAX as u64) as i64;
+    let f10 = f % 10;
+    let f100 = f % 100;
+    match language {
+        // rules on `n`, which equals `i` when the fraction digits are zeros
+        "es" | "el" | "hu" | "tr" | "bg" | "eu" | "af" | "sq" | "ka" | "kk" | "az" | "nb" | "no" | "nn" => {
+            if i == 1 && f == 0 { One } else { Other }
+        },
+        "ar" | "ga" | "cy" | "be" => {
+            if f == 0 { plural_category(language, integer) } else { Other }
+        },
+        "da" => {
+            if i <= 1 && (f != 0 || i == 1) { One } else { Other }
+        },
+        "fr" | "pt" => {
+            if i <= 1 { One } else { Other }
+        },
+        "hi" | "bn" | "fa" | "gu" | "kn" | "am" | "zu" => {
+            if i == 0 || (i == 1 && f == 0) { One } else { Other }
+        },
+        "cs" | "sk" => Many,
+        "lt" => {
+            if f != 0 { Many } else { plural_category(language, integer) }
+        },
+        "lv" => {
+            if f == 0 { plural_category(language, integer) }
+            else if v == 2 && (11..=19).contains(&f100) { Zero }
+            else if f10 == 1 && (v != 2 || f100 != 11) { One }
+            else { Other }
+        },
+        "hr" | "sr" | "bs" => {
+            if f10 == 1 && f100 != 11 { One }
+            else if (2..=4).contains(&f10) && !(12..=14).contains(&f100) { Few }
+            else { Other }
+        },
+        "ro" | "sl" => Few,
+        _ => Other,
+    }
+}
@@ -0,0 +1,23 @@
+// Pointer and bound aliases selected by the `sync` feature.
+
+/// Shared pointer used internally: `Rc`, or `Arc` with the `sync` feature.
+#[cfg(not(feature = "sync"))]
+pub(crate) type Shared<T> = std::rc::Rc<T>;
+#[cfg(feature = "sync")]
+pub(crate) type Shared<T> = std::sync::Arc<T>;
+
+/// Bound on callbacks and providers stored by a `MessageLocator`.
+/// Every type implements it, except that the `sync` feature
+/// additionally requires `Send + Sync`.
+#[cfg(not(feature = "sync"))]
+pub trait MaybeSendSync {}
+#[cfg(not(feature = "sync"))]
+impl<T: ?Sized> MaybeSendSync for T {}
+
+/// Bound on callbacks and providers stored by a `MessageLocator`.
+/// Every type implements it, except that the `sync` feature
+/// additionally requires `Send + Sync`.
+#[cfg(feature = "sync")]
+pub trait MaybeSendSync: Send + Sync {}
+#[cfg(feature = "sync")]
+impl<T: ?Sized + Send + Sync> MaybeSendSync for T {}
@@ -0,0 +1,147 @@
+use super::{Locale, Localizer, MessageLocator, MessageLocatorFormatArgument, Shared};
+use std::collections::HashMap;
+use std::sync::{mpsc, Mutex, RwLock, RwLockReadGuard};
+
+/// A `MessageLocator` behind a lock, for holding one locator in
+/// many places, such as the widgets of a user interface, and switching
+/// its locale through any of them. Clones refer to the same locator.
+///
+/// Widgets call `subscribe` to receive the new locale whenever
+/// `update_locale` succeeds, so that they can render again.
+///
+/// ```ignore
+/// let shared = SharedMessageLocator::new(msg_locator);
+/// let changes = shared.subscribe();
+/// shared.update_locale(parse_locale("pt-BR").unwrap()).await;
+/// assert_eq!(changes.try_recv().unwrap().standard_tag().to_string(), "pt-BR");
+/// ```
+pub struct SharedMessageLocator {
+    _locator: Shared<RwLock<MessageLocator>>,
+    _subscribers: Shared<Mutex<Vec<mpsc::Sender<Locale>>>>,
+    // serializes `update_locale` calls, so that the last call wins
+    // and subscribers receive the locales in the order they were switched to
+    _updating: Shared<futures::lock::Mutex<()>>,
+}
+
+impl Clone for SharedMessageLocator {
+    /// Clones the handle, referring to the same locator.
+    fn clone(&self) -> Self {
+        Self {
+            _locator: self._locator.clone(),
+            _subscribers: self._subscribers.clone(),
+            _updating: self._updating.clone(),
+        }
+    }
+}
+
+impl SharedMessageLocator {
+    pub fn new(locator: MessageLocator) -> Self {
+        Self {
+            _locator: Shared::new(RwLock::new(locator)),
+            _subscribers: Shared::new(Mutex::new(vec![])),
+            _updating: Shared::new(futures::lock::Mutex::new(())),
+        }
+    }
+
+    /// Locks the locator for reading, such as to call methods
+    /// that this handle does not provide.
+    pub fn read(&self) -> RwLockReadGuard<'_, MessageLocator> {
+        self._locator.read().unwrap()
+    }
+
+    /// Returns a receiver of the locales switched to by `update_locale`.
+    /// A receiver that is dropped is unsubscribed.
+    pub fn subscribe(&self) -> mpsc::Receiver<Locale> {
+        let (sender, receiver) = mpsc::channel();
+        self._subscribers.lock().unwrap().push(sender);
+        receiver
+    }
+
+    /// Attempts to load the specified locale and its fallbacks, then
+    /// notifies the subscribers. The locator stays readable while loading;
+    /// it switches to the new locale only once everything is loaded.
+    /// If the locale is not supported or any resource fails to load,
+    /// nothing changes and the method returns `false`, otherwise `true`.
+    ///
+    /// Concurrent calls take effect one at a time, in the order they were made.
+    pub async fn update_locale(&self, new_locale: Locale) -> bool {
+        let _updating = self._updating.lock().await;
+        let mut locator = self.read().clone();
+        if !locator.update_locale(new_locale).await {
+            return false;
+        }
+        let new_locale = locator.current_locale().unwrap();
+        *self._locator.write().unwrap() = locator;
+        self._subscribers.lock().unwrap().retain(|sender| sender.send(new_locale.clone()).is_ok());
+        true
+    }
+
+    /// Loads the current locale and its fallbacks again, as
+    /// `MessageLocator::reload` does, switching to the fresh assets
+    /// only once everything is loaded. If any resource fails to load,
+    /// the error is logged, nothing changes and the method returns `false`.
+    /// Subscribers are not notified.
+    pub async fn reload(&self) -> bool {
+        let _updating = self._updating.lock().await;
+        let mut locator = self.read().clone();
+        if !locator.reload().await {
+            return false;
+        }
+        *self._locator.write().unwrap() = locator;
+        true
+    }
+
+    /// Spawns a Tokio task that calls `reload` at every interval given by
+    /// `MessageLocatorAssetOptions::reload_interval`, keeping the current
+    /// assets whenever a reload fails. Returns `None` if no interval is set.
+    /// Aborting the returned handle stops the reloads.
+    #[cfg(all(feature = "sync", not(feature = "wasm"), not(target_arch = "wasm32")))]
+    pub fn spawn_auto_reload(&self) -> Option<tokio::task::JoinHandle<()>> {
+        let interval = self.read().reload_interval()?;
+        let shared = self.clone();
+        Some(tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                shared.reload().await;
+            }
+        }))
+    }
+
+    /// Returns the currently loaded locale.
+    pub fn current_locale(&self) -> Option<Locale> {
+        self.read().current_locale()
+    }
+
+    /// Retrieves message by identifier. See `MessageLocator::get`.
+    pub fn get<S: ToString>(&self, id: S) -> String {
+        self.read().get(id)
+    }
+
+    /// Retrieves message by identifier with formatting arguments.
+    /// See `MessageLocator::get_formatted`.
+    pub fn get_formatted<S: ToString>(&self, id: S, options: Vec<&dyn MessageLocatorFormatArgument>) -> String {
+        self.read().get_formatted(id, options)
+    }
+}
+
+impl Localizer for SharedMessageLocator {
+    fn current_locale(&self) -> Option<Locale> {
+        SharedMessageLocator::current_locale(self)
+    }
+
+    fn get(&self, id: &str) -> String {
+        SharedMessageLocator::get(self, id)
+    }
+
+    fn get_formatted(&self, id: &str, vars: &HashMap<String, String>) -> String {
+        SharedMessageLocator::get_formatted(self, id, vec![vars])
+    }
+
+    fn get_opt(&self, id: &str, vars: &HashMap<String, String>) -> Option<String> {
+        self.read().get_opt(id, vec![vars])
+    }
+
+    fn get_plural(&self, id: &str, count: i64, vars: &HashMap<String, String>) -> String {
+        self.read().get_plural(id, count, vec![vars])
+    }
+}
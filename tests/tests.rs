@@ -1,5 +1,85 @@
 use recoyx_message_localization::*;
-use futures_await_test::async_test;
+use std::collections::HashMap;
+
+/// Serves the given files over HTTP from a background thread and returns the base URL.
+/// Unknown paths are answered with `404 Not Found`.
+fn serve_http(files: Vec<(&str, String)>) -> String {
+    let files = files.into_iter().map(|(k, v)| (k.to_string(), v)).collect();
+    serve_shared_http(std::sync::Arc::new(std::sync::Mutex::new(files)))
+}
+
+/// Serves files as `serve_http` does, reading them at each request,
+/// so that they can be changed or removed while being served.
+fn serve_shared_http(files: std::sync::Arc<std::sync::Mutex<HashMap<String, String>>>) -> String {
+    serve_http_with(move |path, _| match files.lock().unwrap().get(path) {
+        Some(body) => format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body),
+        None => String::from("HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"),
+    })
+}
+
+/// Serves `body` at every path over HTTP, answering the first `failures`
+/// requests with `status` instead. Returns the base URL and the request count.
+fn serve_flaky_http(body: &'static str, failures: usize, status: &'static str) -> (String, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+    let requests = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let counter = requests.clone();
+    let url = serve_http_with(move |_, _| {
+        if counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst) < failures {
+            format!("HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status)
+        } else {
+            format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body)
+        }
+    });
+    (url, requests)
+}
+
+/// Serves HTTP from a background thread, answering each request, in its own thread,
+/// with the raw response `respond` builds from the request path and lowercased headers.
+/// Returns the base URL.
+fn serve_http_with<F>(respond: F) -> String
+where
+    F: Fn(&str, &HashMap<String, String>) -> String + Send + Sync + 'static,
+{
+    use std::io::{BufRead, BufReader, Write};
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let respond = std::sync::Arc::new(respond);
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let respond = respond.clone();
+            std::thread::spawn(move || {
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut headers = HashMap::new();
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+                    }
+                }
+                let path = request_line.split_whitespace().nth(1).unwrap_or("/").trim_start_matches('/');
+                let _ = stream.write_all(respond(path, &headers).as_bytes());
+            });
+        }
+    });
+    format!("http://{}", addr)
+}
+
+/// Writes asset files into a fresh temporary directory and returns its path.
+fn temp_assets(name: &str, files: &[(&str, &str)]) -> String {
+    let dir = std::env::temp_dir().join(format!("recoyx_message_localization_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    for (path, content) in files.iter() {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+    dir.to_str().unwrap().to_string()
+}
 
 #[test]
 fn locale_country() {
@@ -26,4 +106,2626 @@ async fn msg_locator() {
     msg_locator.load(None).await;
     assert!(msg_locator.supports_locale(&parse_locale("en-US").unwrap()));
     assert_eq!(msg_locator.get("_.message_id"), "Some message".to_string());
+}
+
+#[allow(dead_code)]
+#[derive(LocalizedEnum)]
+#[localized_enum(prefix = "_.status")]
+enum OrderStatus {
+    Pending,
+    InTransit,
+    Delivered(String),
+}
+
+#[derive(LocalizedEnum)]
+enum Unprefixed {
+    SomeVariant,
+    HTTPError,
+    ParseURL,
+    Utf8Error,
+}
+
+#[test]
+fn localized_enum() {
+    assert_eq!(OrderStatus::Pending.message_key(), "_.status.pending");
+    assert_eq!(OrderStatus::InTransit.message_key(), "_.status.in_transit");
+    assert_eq!(OrderStatus::Delivered("someone".to_string()).message_key(), "_.status.delivered");
+    assert_eq!(Unprefixed::SomeVariant.message_key(), "some_variant");
+    assert_eq!(Unprefixed::HTTPError.message_key(), "http_error");
+    assert_eq!(Unprefixed::ParseURL.message_key(), "parse_url");
+    assert_eq!(Unprefixed::Utf8Error.message_key(), "utf8_error");
+}
+
+#[tokio::test]
+async fn msg_locator_other_fallback() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US", "en"])
+            .default_locale("en-US")
+            .fallbacks(maplit::hashmap! {
+                "en-US" => vec!["en"],
+            })
+            .assets(MessageLocatorAssetOptions::new()
+                .src("./tests/res/lang")
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    // category-level fallback
+    assert_eq!(msg_locator.get_formatted("_.items", vec![&"one"]), "One item".to_string());
+    assert_eq!(msg_locator.get_formatted("_.items", vec![&"few"]), "Several items".to_string());
+    // locale-level fallback
+    let vars = localization_vars!{ "number" => "3" };
+    assert_eq!(msg_locator.get_formatted("_.apples", vec![&"few", &vars]), "Apples (3)".to_string());
+    assert_eq!(msg_locator.get_formatted("_.missing", vec![&"few"]), "_.missing_few".to_string());
+}
+
+#[tokio::test]
+async fn msg_locator_case_insensitive_keys() {
+    let options = MessageLocatorOptions::new();
+    options
+        .supported_locales(vec!["en-US"])
+        .default_locale("en-US")
+        .assets(MessageLocatorAssetOptions::new()
+            .src("./tests/res/lang")
+            .base_file_names(vec!["_"])
+            .load_via(MessageLocatorLoadVia::FileSystem));
+
+    let mut msg_locator = MessageLocator::new(&options);
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get("_.Error.Network"), "Network error".to_string());
+    assert_eq!(msg_locator.get("_.error.network"), "_.error.network".to_string());
+
+    let mut msg_locator = MessageLocator::new(options.case_insensitive_keys(true));
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get("_.error.network"), "Network error".to_string());
+    assert_eq!(msg_locator.get("_.ERROR.Network"), "Network error".to_string());
+}
+
+#[tokio::test]
+async fn msg_locator_key_source() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US"])
+            .default_locale("en-US")
+            .track_key_sources(true)
+            .assets(MessageLocatorAssetOptions::new()
+                .src("./tests/res/lang")
+                .base_file_names(vec!["_", "overlap", "overlap/nested"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    let en_us = parse_locale("en-US").unwrap();
+    assert_eq!(msg_locator.key_source(&en_us, "_.message_id"), Some("_".to_string()));
+    assert_eq!(msg_locator.key_source(&en_us, "overlap.title"), Some("overlap".to_string()));
+    assert_eq!(msg_locator.key_source(&en_us, "overlap.nested.body"), Some("overlap/nested".to_string()));
+    assert_eq!(msg_locator.get("overlap.nested.body"), "Body from overlap_nested".to_string());
+    assert_eq!(msg_locator.key_source(&en_us, "overlap.missing"), None);
+}
+
+#[tokio::test]
+async fn msg_locator_formatters() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US"])
+            .default_locale("en-US")
+            .formatter("format", |value: &str, arg: Option<&str>| {
+                let parts: Vec<&str> = value.split("-").collect();
+                match arg {
+                    Some("short") => format!("{}/{}", parts[2], parts[1]),
+                    Some("long form") => format!("{}/{}/{}", parts[2], parts[1], parts[0]),
+                    _ => value.to_string(),
+                }
+            })
+            .assets(MessageLocatorAssetOptions::new()
+                .src("./tests/res/lang")
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get_formatted("_.price", vec![&localization_vars!{ "price" => "12.5" }]), "Price: 12.50 USD.".to_string());
+    assert_eq!(msg_locator.get_formatted("_.shout", vec![&localization_vars!{ "name" => "Alice" }]), "Hey, ALICE!".to_string());
+    assert_eq!(
+        msg_locator.get_formatted("_.event_date", vec![&localization_vars!{ "date" => "2021-03-09" }]),
+        "Starts on 09/03 and ends on 09/03/2021.".to_string());
+
+    // a pipe that does not start a known formatter is text
+    let en_us = parse_locale("en-US").unwrap();
+    let vars = localization_vars!{ "name" => "Alice", "date" => "2021-03-09", "price" => "12.5" };
+    assert_eq!(msg_locator.format_message(&en_us, "$name|speaker: Hi", &vars), "Alice|speaker: Hi".to_string());
+    assert_eq!(msg_locator.format_message(&en_us, "$name|upper|speaker", &vars), "ALICE|speaker".to_string());
+    assert_eq!(msg_locator.format_message(&en_us, "$date|format:short|$price|currency:USD", &vars), "09/03|12.50 USD".to_string());
+    assert_eq!(msg_locator.format_message(&en_us, "${name|speaker}", &vars), "Alice".to_string());
+
+    // currency follows the number conventions of the locale
+    let pt_br = parse_locale("pt-BR").unwrap();
+    let vars = localization_vars!{ "price" => "1234.5" };
+    assert_eq!(msg_locator.format_message(&en_us, "$price|currency:USD", &vars), "1,234.50 USD".to_string());
+    assert_eq!(msg_locator.format_message(&pt_br, "$price|currency:BRL", &vars), "1.234,50 BRL".to_string());
+}
+
+#[tokio::test]
+async fn msg_locator_preload() {
+    let src = temp_assets("preload", &[
+        ("en-US/_.json", r#"{ "hello": "Hello" }"#),
+        ("pt-BR/_.json", r#"{ "hello": "Olá" }"#),
+        ("ja/_.json", r#"{ "hello": "こんにちは" }"#),
+    ]);
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US", "pt-BR", "ja"])
+            .default_locale("en-US")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["_"])
+                .clean_unused(false)
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.preload(&[parse_locale("en-US").unwrap(), parse_locale("pt-BR").unwrap()]).await.is_ok());
+    assert!(msg_locator.current_locale().is_none());
+
+    // preloaded locales must not hit the loader again
+    std::fs::remove_dir_all(&src).unwrap();
+    assert!(msg_locator.update_locale(parse_locale("pt-BR").unwrap()).await);
+    assert_eq!(msg_locator.get("_.hello"), "Olá".to_string());
+    assert!(msg_locator.update_locale(parse_locale("en-US").unwrap()).await);
+    assert_eq!(msg_locator.get("_.hello"), "Hello".to_string());
+    assert!(!msg_locator.update_locale(parse_locale("ja").unwrap()).await);
+}
+
+#[tokio::test]
+async fn msg_locator_load_all() {
+    let src = temp_assets("load_all", &[
+        ("en-US/_.json", r#"{ "hello": "Hello" }"#),
+        ("pt-BR/_.json", r#"{ "hello": "Olá" }"#),
+        ("ja/_.json", r#"{ "hello": "こんにちは" }"#),
+    ]);
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US", "pt-BR", "ja"])
+            .default_locale("en-US")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["_"])
+                .clean_unused(false)
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load_all().await);
+    assert!(msg_locator.current_locale().is_none());
+
+    std::fs::remove_dir_all(&src).unwrap();
+    assert!(msg_locator.update_locale(parse_locale("ja").unwrap()).await);
+    assert_eq!(msg_locator.get("_.hello"), "こんにちは".to_string());
+    assert!(msg_locator.update_locale(parse_locale("pt-BR").unwrap()).await);
+    assert_eq!(msg_locator.get("_.hello"), "Olá".to_string());
+    assert!(msg_locator.try_load_all().await.is_ok());
+}
+
+#[tokio::test]
+async fn msg_locator_yaml_assets() {
+    let src = temp_assets("yaml", &[
+        ("en-US/_.yaml", "greeting: Hello, $name\nfarewell: >-\n  See you\n  soon\nnested:\n  message: Nested\n"),
+    ]);
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US"])
+            .default_locale("en-US")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["_"])
+                .format(AssetFormat::Yaml)
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.preflight().is_empty());
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get_formatted("_.greeting", vec![&localization_vars!{ "name" => "Ana" }]), "Hello, Ana".to_string());
+    assert_eq!(msg_locator.get("_.farewell"), "See you soon".to_string());
+    assert_eq!(msg_locator.get("_.nested.message"), "Nested".to_string());
+}
+
+#[tokio::test]
+async fn msg_locator_search() {
+    let src = temp_assets("search", &[
+        ("fr/_.json", r#"{ "help": { "cv": "Envoyer votre RÉSUMÉ", "menu": "Ouvrir le menu" } }"#),
+        ("en/_.json", r#"{ "help": { "cv": "Send your resume", "upload": "Upload a résumé" } }"#),
+    ]);
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["fr", "en"])
+            .default_locale("fr")
+            .fallback("fr", vec!["en"])
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.search("Resume"), vec![
+        ("_.help.cv".to_string(), "Envoyer votre RÉSUMÉ".to_string()),
+        ("_.help.upload".to_string(), "Upload a résumé".to_string()),
+    ]);
+    assert!(msg_locator.search("absent").is_empty());
+}
+
+#[tokio::test]
+async fn msg_locator_path_for() {
+    let src = temp_assets("path_for", &[
+        ("en_us/_.json", r#"{ "hello": "Hello" }"#),
+        ("pt-BR/_.json", r#"{ "hello": "Olá" }"#),
+    ]);
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US", "pt-BR"])
+            .default_locale("en-US")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["_"])
+                .path_for("en-US", "en_us")
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get("_.hello"), "Hello".to_string());
+    assert!(msg_locator.update_locale(parse_locale("pt-BR").unwrap()).await);
+    assert_eq!(msg_locator.get("_.hello"), "Olá".to_string());
+}
+
+#[tokio::test]
+async fn msg_locator_get_list() {
+    let src = temp_assets("get_list", &[
+        ("en/_.json", r#"{ "tips": ["Press $$ to pay", "Use search"], "mixed": ["a", 1], "title": "Tips" }"#),
+    ]);
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en"])
+            .default_locale("en")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get_list("_.tips"), vec!["Press $ to pay".to_string(), "Use search".to_string()]);
+    assert_eq!(msg_locator.get("_.tips"), "_.tips".to_string());
+    assert!(msg_locator.get_list("_.mixed").is_empty());
+    assert!(msg_locator.get_list("_.title").is_empty());
+    assert!(msg_locator.get_list("_.missing").is_empty());
+}
+
+#[tokio::test]
+async fn msg_locator_file_name_for() {
+    let src = temp_assets("file_name_for", &[
+        ("en/messages_en.json", r#"{ "hello": "Hello" }"#),
+        ("pt-BR/_.json", r#"{ "hello": "Olá" }"#),
+    ]);
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en", "pt-BR"])
+            .default_locale("en")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["_"])
+                .file_name_for("en", "_", "messages_en")
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.preflight().is_empty());
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get("_.hello"), "Hello".to_string());
+    assert!(msg_locator.update_locale(parse_locale("pt-BR").unwrap()).await);
+    assert_eq!(msg_locator.get("_.hello"), "Olá".to_string());
+}
+
+#[tokio::test]
+async fn msg_locator_validate_messages() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US"])
+            .default_locale("en-US")
+            .assets(MessageLocatorAssetOptions::new()
+                .src("./tests/res/invalid")
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    let keys: Vec<String> = msg_locator.validate_messages(&parse_locale("en-US").unwrap()).into_iter().map(|(k, _)| k).collect();
+    assert_eq!(keys, vec!["_.unclosed", "_.unclosed_branch", "_.unknown_keyword", "_.unopened"]);
+}
+
+#[cfg(feature = "datetime")]
+#[tokio::test]
+async fn msg_locator_date_time_arg() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US"])
+            .default_locale("en-US")
+            .assets(MessageLocatorAssetOptions::new()
+                .src("./tests/res/lang")
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    let instant = chrono::DateTime::parse_from_rfc3339("2021-03-09T15:30:00Z").unwrap();
+    let sao_paulo = DateTimeArg::new("date", instant.with_timezone(&chrono_tz::America::Sao_Paulo));
+    let tokyo = DateTimeArg::new("date", instant.with_timezone(&chrono_tz::Asia::Tokyo));
+    assert_eq!(msg_locator.get_formatted("_.event_starts", vec![&sao_paulo]), "Starts at 03/09/2021 12:30 PM.".to_string());
+    assert_eq!(msg_locator.get_formatted("_.event_starts", vec![&tokyo]), "Starts at 03/10/2021 12:30 AM.".to_string());
+}
+
+#[cfg(feature = "datetime")]
+#[test]
+fn msg_locator_format_formatter() {
+    let msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US", "pt-BR", "de"])
+            .default_locale("en-US")
+    ); // msg_locator
+    let (en_us, pt_br, de) = (parse_locale("en-US").unwrap(), parse_locale("pt-BR").unwrap(), parse_locale("de").unwrap());
+    let vars = localization_vars!{ "day" => "2021-03-09", "starts" => "2021-03-09T15:30:00-03:00", "local" => "2021-03-09T15:30", "name" => "Ana" };
+    assert_eq!(msg_locator.format_message(&en_us, "$starts|format", &vars), "03/09/2021 03:30 PM".to_string());
+    assert_eq!(msg_locator.format_message(&pt_br, "$starts|format", &vars), "09/03/2021 15:30".to_string());
+    assert_eq!(msg_locator.format_message(&de, "$local|format:date", &vars), "09.03.2021".to_string());
+    assert_eq!(msg_locator.format_message(&de, "$local|format:time", &vars), "15:30".to_string());
+    assert_eq!(msg_locator.format_message(&pt_br, "$day|format", &vars), "09/03/2021".to_string());
+    // values that are not dates, or lack the requested part, are kept
+    assert_eq!(msg_locator.format_message(&pt_br, "$day|format:time", &vars), "2021-03-09".to_string());
+    assert_eq!(msg_locator.format_message(&pt_br, "$name|format", &vars), "Ana".to_string());
+}
+
+#[cfg(debug_assertions)]
+#[tokio::test]
+#[should_panic(expected = "missing translation key: _.nonexistent in locale en-US")]
+async fn msg_locator_get_required() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US"])
+            .default_locale("en-US")
+            .assets(MessageLocatorAssetOptions::new()
+                .src("./tests/res/lang")
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get_required("_.message_id"), "Some message".to_string());
+    msg_locator.get_required("_.nonexistent");
+}
+
+#[tokio::test]
+async fn msg_locator_shared_base_files() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US", "en"])
+            .default_locale("en-US")
+            .assets(MessageLocatorAssetOptions::new()
+                .src("./tests/res/lang")
+                .base_file_names(vec!["_"])
+                .shared_base_files(vec!["brand"], "./tests/res/shared")
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get("brand.name"), "Recoyx".to_string());
+    assert_eq!(msg_locator.get("_.message_id"), "Some message".to_string());
+    assert!(msg_locator.update_locale(parse_locale("en").unwrap()).await);
+    assert_eq!(msg_locator.get("brand.slogan"), "Flexible message localization".to_string());
+}
+
+#[test]
+fn asset_diff_round_trip() {
+    let old = serde_json::json!({
+        "_": { "title": "Title", "body": "Body", "removed": "Removed" },
+        "menu": { "items": ["a", "b"] },
+        "gone": { "nested": { "deep": "Deep" } },
+    });
+    let new = serde_json::json!({
+        "_": { "title": "New title", "body": "Body", "added": "Added" },
+        "menu": { "items": ["a", "b", "c"] },
+        "brand": "Recoyx",
+    });
+    let diff = diff_assets(&old, &new);
+    assert_eq!(diff.added.len(), 2);
+    assert_eq!(diff.added["_.added"], serde_json::json!("Added"));
+    assert_eq!(diff.removed.len(), 2);
+    assert!(diff.removed.contains_key("gone.nested.deep"));
+    assert_eq!(diff.changed.len(), 2);
+    assert_eq!(diff.changed["_.title"], (serde_json::json!("Title"), serde_json::json!("New title")));
+
+    let mut patched = old.clone();
+    apply_patch(&mut patched, &diff);
+    assert_eq!(patched, new);
+    assert!(diff_assets(&patched, &new).is_empty());
+
+    // keys containing the separator or a backslash
+    let old = serde_json::json!({ "files": { "my.config": "Config", "back\\slash": "Backslash" } });
+    let new = serde_json::json!({ "files": { "my.config": "New config", "back\\slash": "New backslash", "v1.2": "Version" } });
+    let diff = diff_assets(&old, &new);
+    assert_eq!(diff.added["files.v1\\.2"], serde_json::json!("Version"));
+    assert!(diff.changed.contains_key("files.my\\.config"));
+    assert!(diff.changed.contains_key("files.back\\\\slash"));
+    let mut patched = old.clone();
+    apply_patch(&mut patched, &diff);
+    assert_eq!(patched, new);
+}
+
+#[tokio::test]
+async fn msg_locator_get_opt() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US"])
+            .default_locale("en-US")
+            .assets(MessageLocatorAssetOptions::new()
+                .src("./tests/res/lang")
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert_eq!(msg_locator.get_opt("_.message_id", vec![]), None);
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get_opt("_.message_id", vec![]), Some("Some message".to_string()));
+    assert_eq!(msg_locator.get_opt("_.parameterized", vec![&localization_vars!{ "x" => "foo" }]), Some("Here: foo".to_string()));
+    assert_eq!(msg_locator.get_opt("_.nonexistent", vec![]), None);
+    assert_eq!(msg_locator.get("_.nonexistent"), "_.nonexistent".to_string());
+}
+
+#[test]
+fn msg_locator_truthiness() {
+    let msg_locator = MessageLocator::new(&MessageLocatorOptions::new());
+    for value in ["", "0", "false", "no", "  FALSE ", "No"].iter() {
+        assert!(!msg_locator.is_truthy(value), "{:?} should be falsy", value);
+    }
+    for value in ["1", "yes", "true", "off", "00", "undefined"].iter() {
+        assert!(msg_locator.is_truthy(value), "{:?} should be truthy", value);
+    }
+
+    let msg_locator = MessageLocator::new(MessageLocatorOptions::new()
+        .falsy_values(maplit::hashset! { String::from("off"), String::from("") }));
+    assert!(!msg_locator.is_truthy("OFF"));
+    assert!(!msg_locator.is_truthy(""));
+    assert!(msg_locator.is_truthy("false"));
+    assert!(msg_locator.is_truthy("0"));
+}
+
+#[tokio::test]
+async fn msg_locator_bundle() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US"])
+            .default_locale("en-US")
+            .assets(MessageLocatorAssetOptions::new()
+                .src("./tests/res/lang")
+                .base_file_names(vec!["_", "overlap"])
+                .shared_base_files(vec!["brand"], "./tests/res/shared")
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    let bundle = msg_locator.compile_bundle(&parse_locale("en-US").unwrap());
+    let src = temp_assets("bundle", &[]);
+    std::fs::create_dir_all(&src).unwrap();
+    std::fs::write(format!("{}/en-US.bundle", src), bundle).unwrap();
+
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US"])
+            .default_locale("en-US")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .format(AssetFormat::Bundle)
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get("_.message_id"), "Some message".to_string());
+    assert_eq!(msg_locator.get("overlap.nested.body"), "Body from overlap".to_string());
+    assert_eq!(msg_locator.get("brand.name"), "Recoyx".to_string());
+}
+
+#[tokio::test]
+async fn msg_locator_locales_by_completeness() {
+    let src = temp_assets("completeness", &[
+        ("en-US/_.json", r#"{ "a": "A", "b": "B", "c": "C", "d": "D" }"#),
+        ("pt-BR/_.json", r#"{ "a": "A", "b": "B", "c": "C" }"#),
+        ("ja/_.json", r#"{ "a": "A", "extra": "Extra" }"#),
+        ("fr/_.json", r#"{}"#),
+    ]);
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US", "pt-BR", "ja", "fr"])
+            .default_locale("en-US")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    let en_us = parse_locale("en-US").unwrap();
+    assert!(msg_locator.preload(&[en_us.clone(), parse_locale("pt-BR").unwrap(), parse_locale("ja").unwrap()]).await.is_ok());
+    let r: Vec<(String, f64)> = msg_locator.locales_by_completeness(&en_us).into_iter()
+        .map(|(l, ratio)| (l.standard_tag().to_string(), ratio)).collect();
+    assert_eq!(r, vec![
+        ("en-US".to_string(), 1.0),
+        ("pt-BR".to_string(), 0.75),
+        ("ja".to_string(), 0.25),
+    ]);
+}
+
+#[tokio::test]
+async fn msg_locator_response_transform() {
+    let src = serve_http(vec![
+        ("en-US/_.json", r#"{ "data": { "hello": "Hello" }, "meta": { "version": 3 } }"#.to_string()),
+    ]);
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US"])
+            .default_locale("en-US")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["_"])
+                .response_transform(Box::new(|root| root["data"].clone()))
+                .load_via(MessageLocatorLoadVia::Http))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get("_.hello"), "Hello".to_string());
+    assert_eq!(msg_locator.get("_.meta.version"), "_.meta.version".to_string());
+}
+
+struct UppercaseProvider {
+    calls: std::sync::atomic::AtomicUsize,
+}
+
+#[cfg_attr(not(feature = "sync"), async_trait::async_trait(?Send))]
+#[cfg_attr(feature = "sync", async_trait::async_trait)]
+impl FallbackProvider for UppercaseProvider {
+    async fn translate(&self, locale: &Locale, id: &str, source: Option<&str>) -> Option<String> {
+        self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        if id == "_.nonexistent" {
+            return None;
+        }
+        Some(format!("{} [{}]", source.unwrap_or(id).to_uppercase(), locale.standard_tag()))
+    }
+}
+
+#[tokio::test]
+async fn msg_locator_fallback_provider() {
+    let provider = std::sync::Arc::new(UppercaseProvider { calls: std::sync::atomic::AtomicUsize::new(0) });
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US", "en"])
+            .default_locale("en-US")
+            .fallback_provider(provider.clone())
+            .assets(MessageLocatorAssetOptions::new()
+                .src("./tests/res/lang")
+                .base_file_names(vec!["_"])
+                .clean_unused(false)
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert!(msg_locator.update_locale(parse_locale("en").unwrap()).await);
+    assert_eq!(msg_locator.get_async("_.message_id", vec![]).await, "Some generic message".to_string());
+    assert_eq!(msg_locator.get_async("_.parameterized", vec![]).await, "HERE: $X [en]".to_string());
+    assert_eq!(msg_locator.get_async("_.parameterized", vec![]).await, "HERE: $X [en]".to_string());
+    assert_eq!(msg_locator.get_async("_.nonexistent", vec![]).await, "_.nonexistent".to_string());
+    assert_eq!(msg_locator.get_async("_.nonexistent", vec![]).await, "_.nonexistent".to_string());
+    assert_eq!(provider.calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+}
+
+#[test]
+fn msg_locator_preflight() {
+    let src = temp_assets("preflight", &[
+        ("en-US/_.json", r#"{"title": "Title"}"#),
+        ("en-US/menu.json", "[]"),
+        ("en/_.json", "  "),
+        ("pt-BR/_.json", r#"{"title": "#),
+        ("shared/brand.json", r#"{"name": "Recoyx"}"#),
+    ]);
+    std::fs::write(format!("{}/pt-BR/menu.json", src), [0xff, 0xfe]).unwrap();
+    let msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US", "en", "pt-BR"])
+            .default_locale("en-US")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["_", "menu"])
+                .shared_base_files(vec!["brand", "legal"], format!("{}/shared", src))
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    let issues: Vec<(String, Option<String>, PreflightProblem)> = msg_locator.preflight().into_iter()
+        .map(|issue| (issue.path[src.len()..].to_string(), issue.locale.map(|l| l.standard_tag().to_string()), issue.problem))
+        .collect();
+    assert_eq!(issues.len(), 6);
+    assert_eq!(issues[0], ("/shared/legal.json".to_string(), None, PreflightProblem::Missing));
+    assert_eq!(issues[1], ("/en/_.json".to_string(), Some("en".to_string()), PreflightProblem::Empty));
+    assert_eq!(issues[2], ("/en/menu.json".to_string(), Some("en".to_string()), PreflightProblem::Missing));
+    assert_eq!(issues[3], ("/en-US/menu.json".to_string(), Some("en-US".to_string()), PreflightProblem::NotAnObject));
+    assert!(matches!(issues[4].2, PreflightProblem::Malformed(_)));
+    assert_eq!(issues[4].0, "/pt-BR/_.json".to_string());
+    assert_eq!(issues[5], ("/pt-BR/menu.json".to_string(), Some("pt-BR".to_string()), PreflightProblem::InvalidUtf8));
+
+    let src = temp_assets("preflight_single_file", &[
+        ("en.json", r#"{"title": "Title"}"#),
+        ("pt-BR.json", "[]"),
+    ]);
+    let new_locator = |assets: &MessageLocatorAssetOptions| MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en", "pt-BR", "ja"])
+            .default_locale("en")
+            .assets(assets.base_file_names(vec!["_"]))
+    ); // new_locator
+    let issues = |msg_locator: MessageLocator| -> Vec<(String, Option<String>, PreflightProblem)> {
+        msg_locator.preflight().into_iter().map(|issue| (issue.path, issue.locale.map(|l| l.standard_tag().to_string()), issue.problem)).collect()
+    };
+    assert_eq!(issues(new_locator(MessageLocatorAssetOptions::new().src(&src).single_file(true).load_via(MessageLocatorLoadVia::FileSystem))), vec![
+        (format!("{}/ja.json", src), Some("ja".to_string()), PreflightProblem::Missing),
+        (format!("{}/pt-BR.json", src), Some("pt-BR".to_string()), PreflightProblem::NotAnObject),
+    ]);
+    let memory = maplit::hashmap! {
+        "en/_".to_string() => serde_json::json!({ "title": "Title" }),
+        "pt-BR/_".to_string() => serde_json::json!([]),
+    };
+    assert_eq!(issues(new_locator(MessageLocatorAssetOptions::new().memory(memory).load_via(MessageLocatorLoadVia::Memory))), vec![
+        ("ja/_".to_string(), Some("ja".to_string()), PreflightProblem::Missing),
+        ("pt-BR/_".to_string(), Some("pt-BR".to_string()), PreflightProblem::NotAnObject),
+    ]);
+    let static_assets = maplit::hashmap! {
+        ("en".to_string(), "_".to_string()) => r#"{"title": "Title"}"#,
+        ("ja".to_string(), "_".to_string()) => "",
+        ("pt-BR".to_string(), "_".to_string()) => r#"{"title": "#,
+    };
+    let static_issues = issues(new_locator(MessageLocatorAssetOptions::new().static_assets(static_assets).load_via(MessageLocatorLoadVia::Static)));
+    assert_eq!(static_issues[0], ("ja/_".to_string(), Some("ja".to_string()), PreflightProblem::Empty));
+    assert!(matches!(static_issues[1].2, PreflightProblem::Malformed(_)));
+    assert_eq!(static_issues.len(), 2);
+    assert_eq!(issues(new_locator(MessageLocatorAssetOptions::new().src("http://localhost").load_via(MessageLocatorLoadVia::Http))), vec![
+        ("http://localhost".to_string(), None, PreflightProblem::Unsupported),
+    ]);
+}
+
+#[tokio::test]
+async fn msg_locator_direction_override() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US"])
+            .default_locale("en-US")
+            .assets(MessageLocatorAssetOptions::new()
+                .src("./tests/res/lang")
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert!(msg_locator.current_direction() == Direction::LeftToRight);
+    msg_locator.set_direction_override(Some(Direction::RightToLeft));
+    assert!(msg_locator.current_direction() == Direction::RightToLeft);
+    assert!(msg_locator.direction() == Direction::LeftToRight);
+    msg_locator.set_direction_override(None);
+    assert!(msg_locator.current_direction() == Direction::LeftToRight);
+}
+
+#[tokio::test]
+async fn msg_locator_direction() {
+    let src = temp_assets("direction", &[
+        ("en/_.json", r#"{}"#),
+        ("ar/_.json", r#"{}"#),
+    ]);
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["ar", "en"])
+            .default_locale("ar")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    // the default locale is used before any locale is loaded
+    assert!(msg_locator.direction() == Direction::RightToLeft);
+    assert!(msg_locator.load(Some(parse_locale("en").unwrap())).await);
+    assert!(msg_locator.direction() == Direction::LeftToRight);
+    assert!(msg_locator.load(Some(parse_locale("ar").unwrap())).await);
+    assert!(msg_locator.direction() == Direction::RightToLeft);
+}
+
+#[tokio::test]
+async fn msg_locator_find() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US", "en"])
+            .default_locale("en-US")
+            .fallbacks(maplit::hashmap! {
+                "en-US" => vec!["en"],
+            })
+            .assets(MessageLocatorAssetOptions::new()
+                .src("./tests/res/lang")
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.find("NETWORK"), vec![("_.Error.Network".to_string(), "Network error".to_string())]);
+    assert_eq!(msg_locator.find("message_id"), vec![("_.message_id".to_string(), "Some message".to_string())]);
+    assert_eq!(msg_locator.find("apples_other"), vec![("_.apples_other".to_string(), "Apples ($number)".to_string())]);
+    assert!(msg_locator.find("_").is_empty());
+}
+
+#[tokio::test]
+async fn msg_locator_escaped_ids() {
+    let src = temp_assets("escaped_ids", &[
+        ("en-US/files.json", r#"{"my.config": {"title": "Config title"}, "my": {"config": {"title": "Nested title"}}, "back\\slash": "Backslash"}"#),
+    ]);
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US"])
+            .default_locale("en-US")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["files"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get("files.my\\.config.title"), "Config title".to_string());
+    assert_eq!(msg_locator.get("files.my.config.title"), "Nested title".to_string());
+    assert_eq!(msg_locator.get("files.back\\\\slash"), "Backslash".to_string());
+    assert!(msg_locator.message_keys(&parse_locale("en-US").unwrap()).contains(&"files.my\\.config.title".to_string()));
+
+    // escaped keys survive a bundle round trip
+    let bundle = msg_locator.compile_bundle(&parse_locale("en-US").unwrap());
+    let src = temp_assets("escaped_ids_bundle", &[]);
+    std::fs::create_dir_all(&src).unwrap();
+    std::fs::write(format!("{}/en-US.bundle", src), bundle).unwrap();
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US"])
+            .default_locale("en-US")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .format(AssetFormat::Bundle)
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get("files.my\\.config.title"), "Config title".to_string());
+    assert_eq!(msg_locator.get("files.my.config.title"), "Nested title".to_string());
+    assert_eq!(msg_locator.get("files.back\\\\slash"), "Backslash".to_string());
+}
+
+#[cfg(not(feature = "sync"))]
+#[tokio::test]
+async fn msg_locator_on_locale_change() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US", "en", "pt-BR"])
+            .default_locale("en-US")
+            .assets(MessageLocatorAssetOptions::new()
+                .src("./tests/res/lang")
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    let changes = std::rc::Rc::new(std::cell::RefCell::new(Vec::<String>::new()));
+    let changes_1 = changes.clone();
+    msg_locator.on_locale_change(Box::new(move |locale| changes_1.borrow_mut().push(format!("1:{}", locale.standard_tag()))));
+    let changes_2 = changes.clone();
+    msg_locator.on_locale_change(Box::new(move |locale| changes_2.borrow_mut().push(format!("2:{}", locale.standard_tag()))));
+    assert!(msg_locator.load(None).await);
+    assert!(msg_locator.update_locale(parse_locale("en").unwrap()).await);
+    // pt-BR has no assets, so loading it fails
+    assert!(!msg_locator.update_locale(parse_locale("pt-BR").unwrap()).await);
+    assert_eq!(*changes.borrow(), vec!["1:en-US", "2:en-US", "1:en", "2:en"]);
+}
+
+/// With the `sync` feature, callbacks must be `Send + Sync`.
+#[cfg(feature = "sync")]
+#[tokio::test]
+async fn msg_locator_on_locale_change_sync() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US", "en"])
+            .default_locale("en-US")
+            .assets(MessageLocatorAssetOptions::new()
+                .src("./tests/res/lang")
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    let changes = std::sync::Arc::new(std::sync::Mutex::new(Vec::<String>::new()));
+    let changes_1 = changes.clone();
+    msg_locator.on_locale_change(Box::new(move |locale| changes_1.lock().unwrap().push(locale.standard_tag().to_string())));
+    assert!(msg_locator.load(None).await);
+    assert!(msg_locator.update_locale(parse_locale("en").unwrap()).await);
+    assert_eq!(*changes.lock().unwrap(), vec!["en-US", "en"]);
+}
+
+#[tokio::test]
+async fn msg_locator_try_load() {
+    let src = temp_assets("try_load", &[
+        ("en-US/_.json", r#"{"title": "Title"}"#),
+        ("en/_.json", r#"{"title": "#),
+    ]);
+    let new_locator = |fallbacks: Vec<&str>| MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US", "en", "pt-BR"])
+            .default_locale("en-US")
+            .fallbacks(maplit::hashmap! { "en-US" => fallbacks })
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(new_locator(vec![]).try_load(None).await.is_ok());
+
+    let error = new_locator(vec!["en"]).try_load(None).await.unwrap_err();
+    assert!(matches!(error, MessageLoadError::Parse { .. }));
+    assert_eq!(error.path(), Some(format!("{}/en/_.json", src).as_ref()));
+
+    let mut msg_locator = new_locator(vec![]);
+    let error = msg_locator.try_load(Some(parse_locale("pt-BR").unwrap())).await.unwrap_err();
+    assert!(matches!(error, MessageLoadError::Io { .. }));
+    assert_eq!(error.path(), Some(format!("{}/pt-BR/_.json", src).as_ref()));
+    assert!(msg_locator.current_locale().is_none());
+
+
+    let base = serve_http(vec![("/en-US/_.json", r#"{"title": "Title"}"#.to_string())]);
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US", "en"])
+            .default_locale("en-US")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&base)
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::Http))
+    ); // msg_locator
+    let error = msg_locator.try_load(Some(parse_locale("en").unwrap())).await.unwrap_err();
+    assert!(matches!(error, MessageLoadError::Http { .. }));
+    assert_eq!(error.path(), Some(format!("{}/en/_.json", base).as_ref()));
+
+    // the fetch API is unsupported without the wasm feature
+    #[cfg(not(feature = "wasm"))]
+    {
+        let mut msg_locator = MessageLocator::new(
+            MessageLocatorOptions::new()
+                .supported_locales(vec!["en-US"])
+                .default_locale("en-US")
+                .assets(MessageLocatorAssetOptions::new()
+                    .src(&base)
+                    .base_file_names(vec!["_"])
+                    .load_via(MessageLocatorLoadVia::Fetch))
+        ); // msg_locator
+        let error = msg_locator.try_load(None).await.unwrap_err();
+        assert!(matches!(&error, MessageLoadError::Io { source, .. } if source.kind() == std::io::ErrorKind::Unsupported));
+    }
+}
+
+#[tokio::test]
+async fn msg_locator_get_plural() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US"])
+            .default_locale("en-US")
+            .assets(MessageLocatorAssetOptions::new()
+                .src("./tests/res/lang")
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    let vars = localization_vars!{ "folder" => "Inbox" };
+    assert_eq!(msg_locator.get_plural("_.inbox.messages", 1, vec![&vars]), "1 message in Inbox".to_string());
+    assert_eq!(msg_locator.get_plural("_.inbox.messages", 5, vec![&vars]), "5 messages in Inbox".to_string());
+    assert_eq!(msg_locator.get_plural("_.inbox.missing", 5, vec![]), "_.inbox.missing".to_string());
+
+    let src = temp_assets("get_plural", &[
+        ("ru/_.json", r#"{"files": {"one": "$count файл", "few": "$count файла", "many": "$count файлов"}}"#),
+        ("ar/_.json", r#"{"files": {"zero": "لا ملفات", "other": "$count ملف"}}"#),
+        ("ja/_.json", r#"{"files": {"other": "$count ファイル"}}"#),
+    ]);
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["ru", "ar", "ja"])
+            .default_locale("ru")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get_plural("_.files", 21, vec![]), "21 файл".to_string());
+    assert_eq!(msg_locator.get_plural("_.files", 3, vec![]), "3 файла".to_string());
+    assert_eq!(msg_locator.get_plural("_.files", 11, vec![]), "11 файлов".to_string());
+    assert!(msg_locator.update_locale(parse_locale("ar").unwrap()).await);
+    assert_eq!(msg_locator.get_plural("_.files", 0, vec![]), "لا ملفات".to_string());
+    // `few` is missing, so `other` is used
+    assert_eq!(msg_locator.get_plural("_.files", 3, vec![]), "3 ملف".to_string());
+    assert!(msg_locator.update_locale(parse_locale("ja").unwrap()).await);
+    assert_eq!(msg_locator.get_plural("_.files", 1, vec![]), "1 ファイル".to_string());
+}
+
+#[tokio::test]
+async fn msg_locator_decimal_plurals() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US", "fr", "cs"])
+            .default_locale("en-US")
+            .assets(MessageLocatorAssetOptions::new()
+                .src("./tests/res/lang")
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    let vars = localization_vars!{ "folder" => "Inbox" };
+    assert_eq!(msg_locator.get_plural_decimal("_.inbox.messages", "1", vec![&vars]), "1 message in Inbox".to_string());
+    assert_eq!(msg_locator.get_plural_decimal("_.inbox.messages", "1.0", vec![&vars]), "1.0 messages in Inbox".to_string());
+    assert_eq!(msg_locator.get_plural_decimal("_.inbox.messages", "1-2", vec![&vars]), "1-2 messages in Inbox".to_string());
+
+    let (en, fr, cs) = (parse_locale("en").unwrap(), parse_locale("fr").unwrap(), parse_locale("cs").unwrap());
+    let message = "{n, plural, =1 {one star} one {# star} other {# stars}}";
+    let format = |locale: &Locale, n: &str| msg_locator.format_message(locale, message, &localization_vars!{ "n" => n });
+    assert_eq!(format(&en, "1"), "one star".to_string());
+    assert_eq!(format(&en, "1.0"), "one star".to_string());
+    assert_eq!(format(&en, "1000.50"), "1,000.50 stars".to_string());
+    assert_eq!(format(&en, "1-2"), "1–2 stars".to_string());
+    let message = "{n, plural, one {# étoile} other {# étoiles}}";
+    assert_eq!(msg_locator.format_message(&fr, message, &localization_vars!{ "n" => "1.5" }), "1,5 étoile".to_string());
+    assert_eq!(msg_locator.format_message(&fr, message, &localization_vars!{ "n" => "2.5" }), "2,5 étoiles".to_string());
+    let message = "{n, plural, one {# hvězda} few {# hvězdy} many {# hvězdy} other {# hvězd}}";
+    assert_eq!(msg_locator.format_message(&cs, message, &localization_vars!{ "n" => "1.5" }), "1,5 hvězdy".to_string());
+    assert_eq!(msg_locator.format_message(&cs, message, &localization_vars!{ "n" => "5" }), "5 hvězd".to_string());
+    let message = "{n, plural, one {# item} other {# items}}";
+    assert_eq!(msg_locator.format_message(&en, message, &localization_vars!{ "n" => "1.0" }), "1.0 items".to_string());
+    assert_eq!(msg_locator.format_message(&en, "$n|range", &localization_vars!{ "n" => "1000-2000" }), "1,000–2,000".to_string());
+}
+
+#[tokio::test]
+async fn msg_locator_inherit_language_files() {
+    let src = temp_assets("inherit_language_files", &[
+        ("en/common.json", r#"{"color": "Color", "greeting": "Hello", "menu": {"open": "Open", "close": "Close"}}"#),
+        ("en-GB/common.json", r#"{"color": "Colour", "menu": {"close": "Close menu"}}"#),
+    ]);
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-GB"])
+            .default_locale("en-GB")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["common"])
+                .inherit_language_files(true)
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get("common.color"), "Colour".to_string());
+    assert_eq!(msg_locator.get("common.greeting"), "Hello".to_string());
+    assert_eq!(msg_locator.get("common.menu.open"), "Open".to_string());
+    assert_eq!(msg_locator.get("common.menu.close"), "Close menu".to_string());
+    assert!(msg_locator.preflight().is_empty());
+
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-GB"])
+            .default_locale("en-GB")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["common"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get("common.greeting"), "common.greeting".to_string());
+}
+
+#[tokio::test]
+async fn msg_locator_brace_placeholders() {
+    let src = temp_assets("brace_placeholders", &[
+        ("en-US/_.json", r#"{"price": "{item} costs $5, or {price|currency:USD}.", "literal": "Use {{name}} for {name|upper}}}"}"#),
+    ]);
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US"])
+            .default_locale("en-US")
+            .placeholder_style(PlaceholderStyle::Braces)
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    let vars = localization_vars!{ "item" => "Tea", "price" => "5", "name" => "x" };
+    assert_eq!(msg_locator.get_formatted("_.price", vec![&vars]), "Tea costs $5, or 5.00 USD.".to_string());
+    assert_eq!(msg_locator.get_formatted("_.literal", vec![&vars]), "Use {name} for X}".to_string());
+}
+
+#[tokio::test]
+async fn msg_locator_get_value() {
+    let src = temp_assets("get_value", &[
+        ("en-US/_.json", r#"{"menu": {"items": ["Home", "About"]}, "title": "Title"}"#),
+        ("en/_.json", r#"{"theme": {"accent": "blue", "rounded": true}}"#),
+    ]);
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US", "en"])
+            .default_locale("en-US")
+            .fallbacks(maplit::hashmap! {
+                "en-US" => vec!["en"],
+            })
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get_value("_.menu.items"), Some(serde_json::json!(["Home", "About"])));
+    assert_eq!(msg_locator.get_value("_.theme"), Some(serde_json::json!({"accent": "blue", "rounded": true})));
+    assert_eq!(msg_locator.get_value("_.title"), Some(serde_json::json!("Title")));
+    assert_eq!(msg_locator.get_value("_.missing"), None);
+}
+
+#[tokio::test]
+async fn msg_locator_max_format_steps() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US"])
+            .default_locale("en-US")
+            .max_format_steps(3)
+            .formatter("format", |value: &str, _: Option<&str>| value.to_string())
+            .assets(MessageLocatorAssetOptions::new()
+                .src("./tests/res/lang")
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    let vars = localization_vars!{ "date" => "today" };
+    // each placeholder costs one step plus one per formatter
+    assert_eq!(msg_locator.get_formatted("_.event_date", vec![&vars]), "Starts on today and ends on $date|format:long\\ form.".to_string());
+    let vars = localization_vars!{ "count" => "2", "folder" => "Inbox" };
+    assert_eq!(msg_locator.get_formatted("_.inbox.messages.other", vec![&vars]), "2 messages in Inbox".to_string());
+}
+
+#[tokio::test]
+async fn msg_locator_memory() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US", "pt-BR"])
+            .default_locale("en-US")
+            .assets(MessageLocatorAssetOptions::new()
+                .base_file_names(vec!["_", "menu/main"])
+                .shared_base_files(vec!["brand"], "")
+                .memory(maplit::hashmap! {
+                    "en-US/_".to_string() => serde_json::json!({ "message_id": "Some message" }),
+                    "en-US/menu/main".to_string() => serde_json::json!({ "open": "Open" }),
+                    "brand".to_string() => serde_json::json!({ "name": "Recoyx" }),
+                })
+                .load_via(MessageLocatorLoadVia::Memory))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get("_.message_id"), "Some message".to_string());
+    assert_eq!(msg_locator.get("menu.main.open"), "Open".to_string());
+    assert_eq!(msg_locator.get("brand.name"), "Recoyx".to_string());
+    let error = msg_locator.try_load(Some(parse_locale("pt-BR").unwrap())).await.unwrap_err();
+    assert_eq!(error.path(), Some("pt-BR/_"));
+}
+
+#[tokio::test]
+async fn msg_locator_static_assets() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US", "pt-BR"])
+            .default_locale("en-US")
+            .assets(MessageLocatorAssetOptions::new()
+                .base_file_names(vec!["_"])
+                .shared_base_files(vec!["brand"], "")
+                .static_assets(maplit::hashmap! {
+                    ("en-US".to_string(), "_".to_string()) => include_str!("res/lang/en-US/_.json"),
+                    ("pt-BR".to_string(), "_".to_string()) => "{ \"message_id\": ",
+                    ("".to_string(), "brand".to_string()) => r#"{ "name": "Recoyx" }"#,
+                })
+                .load_via(MessageLocatorLoadVia::Static))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get("_.message_id"), "Some message".to_string());
+    assert_eq!(msg_locator.get("brand.name"), "Recoyx".to_string());
+    let error = msg_locator.try_load(Some(parse_locale("pt-BR").unwrap())).await.unwrap_err();
+    assert!(matches!(error, MessageLoadError::Parse { .. }));
+    assert_eq!(error.path(), Some("pt-BR/_"));
+}
+
+#[cfg(feature = "sync")]
+#[test]
+fn msg_locator_send_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<MessageLocator>();
+    assert_send_sync::<Locale>();
+}
+
+#[cfg(feature = "sync")]
+#[test]
+fn msg_load_error_send_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<MessageLoadError>();
+}
+
+// browser fetches are never `Send`, so the `wasm` feature is excluded
+#[cfg(all(feature = "sync", not(feature = "wasm")))]
+#[test]
+fn msg_locator_load_future_send() {
+    fn assert_send<T: Send>(_: T) {}
+    fn check(mut msg_locator: MessageLocator) {
+        assert_send(async move { msg_locator.load(None).await });
+    }
+    let _ = check;
+}
+
+struct MockLocalizer;
+
+impl Localizer for MockLocalizer {
+    fn current_locale(&self) -> Option<Locale> { parse_locale("en").ok() }
+    fn get(&self, id: &str) -> String { format!("[{}]", id) }
+    fn get_formatted(&self, id: &str, vars: &HashMap<String, String>) -> String { format!("[{} {}]", id, vars.len()) }
+    fn get_opt(&self, _id: &str, _vars: &HashMap<String, String>) -> Option<String> { None }
+    fn get_plural(&self, id: &str, count: i64, _vars: &HashMap<String, String>) -> String { format!("[{} {}]", id, count) }
+}
+
+#[tokio::test]
+async fn localizer_trait_object() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US"])
+            .default_locale("en-US")
+            .assets(MessageLocatorAssetOptions::new()
+                .src("./tests/res/lang")
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    let vars = localization_vars!{ "x" => "foo" };
+    let localizers: Vec<Box<dyn Localizer>> = vec![Box::new(msg_locator), Box::new(MockLocalizer)];
+    assert_eq!(localizers[0].get("_.message_id"), "Some message".to_string());
+    assert_eq!(localizers[0].get_formatted("_.parameterized", &vars), "Here: foo".to_string());
+    assert_eq!(localizers[0].get_opt("_.missing", &vars), None);
+    assert_eq!(localizers[0].get_plural("_.inbox.messages", 1, &localization_vars!{ "folder" => "Inbox" }), "1 message in Inbox".to_string());
+    assert_eq!(localizers[1].get("_.message_id"), "[_.message_id]".to_string());
+    assert_eq!(localizers[1].get_formatted("_.parameterized", &vars), "[_.parameterized 1]".to_string());
+    assert!(localizers[1].current_locale() == parse_locale("en").ok());
+}
+
+#[tokio::test]
+async fn msg_locator_has_message() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US", "en"])
+            .default_locale("en-US")
+            .fallbacks(maplit::hashmap! {
+                "en-US" => vec!["en"],
+            })
+            .assets(MessageLocatorAssetOptions::new()
+                .src("./tests/res/lang")
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(!msg_locator.has_message("_.message_id"));
+    assert!(msg_locator.load(None).await);
+    assert!(msg_locator.has_message("_.message_id"));
+    assert!(msg_locator.has_message("_.apples_other"));
+    assert!(!msg_locator.has_message("_.Error"));
+    assert!(!msg_locator.has_message("_.missing"));
+}
+
+#[tokio::test]
+async fn msg_locator_front_matter() {
+    let src = temp_assets("front_matter", &[
+        ("en-US/intro.json", "---\ntitle: Getting started\norder: 1\n---\n{\"welcome\": \"Welcome!\"}"),
+        ("en-US/plain.json", r#"{"text": "No metadata"}"#),
+    ]);
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US"])
+            .default_locale("en-US")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["intro", "plain"])
+                .front_matter(true)
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get("intro.welcome"), "Welcome!".to_string());
+    assert_eq!(msg_locator.metadata("intro"), Some(serde_json::json!({"title": "Getting started", "order": 1})));
+    assert_eq!(msg_locator.get("intro._meta.title"), "Getting started".to_string());
+    assert_eq!(msg_locator.get("plain.text"), "No metadata".to_string());
+    assert_eq!(msg_locator.metadata("plain"), None);
+}
+
+#[tokio::test]
+async fn msg_locator_declension() {
+    let src = temp_assets("declension", &[
+        ("ru/_.json", r#"{"no_items": "Нет $noun|case:genitive.", "given": "Отдано $noun|case:dative."}"#),
+        ("ru/declensions.json", r#"{"файл": {"genitive": "файла", "dative": "файлу"}, "папка": {"genitive": "папки"}}"#),
+    ]);
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["ru"])
+            .default_locale("ru")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["_", "declensions"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get_formatted("_.no_items", vec![&localization_vars!{ "noun" => "файл" }]), "Нет файла.".to_string());
+    assert_eq!(msg_locator.get_formatted("_.given", vec![&localization_vars!{ "noun" => "файл" }]), "Отдано файлу.".to_string());
+    assert_eq!(msg_locator.get_formatted("_.no_items", vec![&localization_vars!{ "noun" => "папка" }]), "Нет папки.".to_string());
+    // missing forms are left as is
+    assert_eq!(msg_locator.get_formatted("_.given", vec![&localization_vars!{ "noun" => "папка" }]), "Отдано папка.".to_string());
+}
+
+#[test]
+fn msg_locator_resolution_plan() {
+    let msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US", "en-GB", "en", "pt-BR", "ja"])
+            .default_locale("en-US")
+            .fallbacks(maplit::hashmap! {
+                "en-GB" => vec!["en-US"],
+                "en-US" => vec!["en"],
+                "pt-BR" => vec!["en-US"],
+            })
+            .assets(MessageLocatorAssetOptions::new()
+                .inherit_language_files(true))
+    ); // msg_locator
+    let plan = |tag: &str| -> Vec<String> {
+        msg_locator.resolution_plan(&parse_locale(tag).unwrap()).iter().map(|l| l.standard_tag().to_string()).collect()
+    };
+    assert_eq!(plan("en-GB"), vec!["en-GB", "en", "en-US"]);
+    assert_eq!(plan("pt-BR"), vec!["pt-BR", "pt", "en-US", "en"]);
+    // unsupported requests are negotiated first
+    assert_eq!(plan("en-AU"), vec!["en"]);
+    assert_eq!(plan("pt-PT"), vec!["pt-BR", "pt", "en-US", "en"]);
+    assert_eq!(plan("fr"), vec!["en-US", "en"]);
+}
+
+#[tokio::test]
+async fn msg_locator_implicit_language_fallback() {
+    let src = temp_assets("implicit_language_fallback", &[
+        ("pt-BR/_.json", r#"{"bus": "Ônibus"}"#),
+        ("pt/_.json", r#"{"bus": "Autocarro", "train": "Comboio"}"#),
+    ]);
+    let new_locator = |implicit: bool| MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["pt-BR", "pt"])
+            .default_locale("pt-BR")
+            // a cycle through the implicit fallback must not loop
+            .fallbacks(maplit::hashmap! { "pt" => vec!["pt-BR"] })
+            .implicit_language_fallback(implicit)
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    let mut msg_locator = new_locator(true);
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get("_.bus"), "Ônibus".to_string());
+    assert_eq!(msg_locator.get("_.train"), "Comboio".to_string());
+    assert_eq!(msg_locator.get("_.plane"), "_.plane".to_string());
+    assert!(msg_locator.has_message("_.train"));
+
+    let mut msg_locator = new_locator(false);
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get("_.train"), "_.train".to_string());
+
+    // the language locale is an alias of a supported regional locale
+    let src = temp_assets("implicit_language_fallback_alias", &[
+        ("en-GB/_.json", r#"{"colour": "Colour"}"#),
+        ("en-US/_.json", r#"{"colour": "Color", "elevator": "Elevator"}"#),
+    ]);
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US", "en-GB"])
+            .default_locale("en-GB")
+            .aliases(maplit::hashmap! { "en" => "en-US" })
+            .implicit_language_fallback(true)
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get("_.colour"), "Colour".to_string());
+    assert_eq!(msg_locator.get("_.elevator"), "Elevator".to_string());
+}
+
+#[tokio::test]
+async fn msg_locator_get_partial() {
+    let src = temp_assets("get_partial", &[
+        ("en-US/_.json", r#"{"greeting": "Dear $title|upper $name, your order $order ships to $name."}"#),
+    ]);
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US"])
+            .default_locale("en-US")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    let vars = localization_vars!{ "order" => "#42" };
+    let (message, unfilled) = msg_locator.get_partial("_.greeting", vec![&vars]);
+    assert_eq!(message, "Dear $title|upper $name, your order #42 ships to $name.".to_string());
+    assert_eq!(unfilled, vec!["title".to_string(), "name".to_string()]);
+    let vars = localization_vars!{ "title" => "dr", "name" => "Ana", "order" => "#42" };
+    assert_eq!(msg_locator.get_partial("_.greeting", vec![&vars]), ("Dear DR Ana, your order #42 ships to Ana.".to_string(), vec![]));
+    assert_eq!(msg_locator.get_partial("_.missing", vec![]), ("_.missing".to_string(), vec![]));
+}
+
+#[tokio::test]
+async fn msg_locator_truncation() {
+    let src = temp_assets("truncation", &[
+        ("de/_.json", r#"{"save": "Änderungen speichern", "ok": "OK", "family": "👨‍👩‍👧‍👦 Familie", "accent": "e\u0301e\u0301e\u0301e\u0301"}"#),
+    ]);
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["de"])
+            .default_locale("de")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get_truncated("_.save", 10), "Änderunge…".to_string());
+    assert_eq!(msg_locator.get_truncated("_.ok", 2), "OK".to_string());
+    assert_eq!(msg_locator.get_truncated("_.family", 3), "👨‍👩‍👧‍👦 …".to_string());
+    assert_eq!(msg_locator.get_truncated("_.accent", 3), "e\u{301}e\u{301}…".to_string());
+    assert_eq!(msg_locator.get_truncated("_.accent", 4), "e\u{301}e\u{301}e\u{301}e\u{301}".to_string());
+
+    let budgets = maplit::hashmap! {
+        "_.save".to_string() => 12,
+        "_.ok".to_string() => 12,
+        "_.accent".to_string() => 3,
+    };
+    assert_eq!(msg_locator.validate_lengths(&parse_locale("de").unwrap(), &budgets), vec![
+        ("_.accent".to_string(), 4),
+        ("_.save".to_string(), 20),
+    ]);
+}
+
+#[tokio::test]
+async fn msg_locator_message_keys() {
+    let src = temp_assets("message_keys", &[
+        ("en/inbox.json", r#"{"title": "Inbox", "messages": {"one": "One message", "other": "$count messages"}, "order": 1, "tags": ["a"]}"#),
+    ]);
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en"])
+            .default_locale("en")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["inbox"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.message_keys(&parse_locale("en").unwrap()).is_empty());
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.message_keys(&parse_locale("en").unwrap()), vec!["inbox.messages.one", "inbox.messages.other", "inbox.title"]);
+}
+
+#[test]
+fn msg_locator_chained_fallbacks() {
+    let msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["fr", "de", "en", "pt-BR", "pt"])
+            .default_locale("en")
+            .fallback("fr", vec!["en"])
+            .fallback("de", vec!["fr"])
+            .fallback("pt-BR", vec!["en"])
+            .fallback("pt-BR", vec!["pt"])
+    ); // msg_locator
+    let plan = |tag: &str| -> Vec<String> {
+        msg_locator.resolution_plan(&parse_locale(tag).unwrap()).iter().map(|l| l.standard_tag().to_string()).collect()
+    };
+    assert_eq!(plan("de"), vec!["de", "fr", "en"]);
+    assert_eq!(plan("pt-BR"), vec!["pt-BR", "pt"]);
+}
+
+/// Checks plural and select output against ICU MessageFormat reference
+/// strings, such as `{n, plural, one {# файл} few {# файла} many {# файлов} other {# файла}}`
+/// and `{gender, select, female {She} male {He} other {They}}`.
+#[tokio::test]
+async fn msg_locator_icu_conformance() {
+    let src = temp_assets("icu_conformance", &[
+        ("en/_.json", r#"{ "files": { "one": "$count file", "other": "$count files" },
+            "pronoun_female": "She", "pronoun_male": "He", "pronoun_other": "They" }"#),
+        ("fr/_.json", r#"{ "files": { "one": "$count fichier", "other": "$count fichiers" } }"#),
+        ("ru/_.json", r#"{ "files": { "one": "$count файл", "few": "$count файла", "many": "$count файлов", "other": "$count файла" } }"#),
+        ("pl/_.json", r#"{ "files": { "one": "$count plik", "few": "$count pliki", "many": "$count plików", "other": "$count pliku" } }"#),
+        ("ar/_.json", r#"{ "files": { "zero": "zero", "one": "one", "two": "two", "few": "few", "many": "many", "other": "other" } }"#),
+    ]);
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en", "fr", "ru", "pl", "ar"])
+            .default_locale("en")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["_"])
+                .clean_unused(false)
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    let reference: Vec<(&str, i64, &str)> = vec![
+        ("en", 0, "0 files"), ("en", 1, "1 file"), ("en", 2, "2 files"),
+        ("fr", 0, "0 fichier"), ("fr", 1, "1 fichier"), ("fr", 2, "2 fichiers"),
+        ("ru", 1, "1 файл"), ("ru", 3, "3 файла"), ("ru", 5, "5 файлов"),
+        ("ru", 11, "11 файлов"), ("ru", 21, "21 файл"), ("ru", 22, "22 файла"),
+        ("pl", 1, "1 plik"), ("pl", 2, "2 pliki"), ("pl", 5, "5 plików"),
+        ("pl", 12, "12 plików"), ("pl", 22, "22 pliki"),
+        ("ar", 0, "zero"), ("ar", 1, "one"), ("ar", 2, "two"),
+        ("ar", 3, "few"), ("ar", 11, "many"), ("ar", 100, "other"),
+    ];
+    for (tag, count, expected) in reference {
+        assert!(msg_locator.update_locale(parse_locale(tag).unwrap()).await);
+        assert_eq!(msg_locator.get_plural("_.files", count, vec![]), expected, "{} {}", tag, count);
+    }
+
+    assert!(msg_locator.update_locale(parse_locale("en").unwrap()).await);
+    assert_eq!(msg_locator.get_formatted("_.pronoun", vec![&"female"]), "She".to_string());
+    assert_eq!(msg_locator.get_formatted("_.pronoun", vec![&"male"]), "He".to_string());
+    assert_eq!(msg_locator.get_formatted("_.pronoun", vec![&"unknown"]), "They".to_string());
+}
+
+#[test]
+fn msg_locator_format_message() {
+    let msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en"])
+            .default_locale("en")
+    ); // msg_locator
+    let en = parse_locale("en").unwrap();
+    let vars = localization_vars!{ "name" => "Ana", "total" => "3.5" };
+    assert_eq!(msg_locator.format_message(&en, "Hi $name|upper, you owe $total|currency:USD ($$)", &vars), "Hi ANA, you owe 3.50 USD ($)".to_string());
+    assert_eq!(msg_locator.format_message(&en, "{n, plural, one {# file} other {# files}}", &vars), "{n, plural, one {# file} other {# files}}".to_string());
+}
+
+#[tokio::test]
+async fn msg_locator_http_timeout_and_headers() {
+    // answers with the value of the X-Token header
+    let url = serve_http_with(|_, headers| {
+        let body = format!(r#"{{ "token": "{}" }}"#, headers.get("x-token").cloned().unwrap_or_default());
+        format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body)
+    });
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en"])
+            .default_locale("en")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(url)
+                .base_file_names(vec!["_"])
+                .http_headers(maplit::hashmap! { "X-Token".to_string() => "secret".to_string() })
+                .load_via(MessageLocatorLoadVia::Http))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get("_.token"), "secret".to_string());
+
+    // accepts connections but answers too late
+    let silent = serve_http_with(|_, _| {
+        std::thread::sleep(std::time::Duration::from_secs(5));
+        String::from("HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+    });
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en"])
+            .default_locale("en")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(silent)
+                .base_file_names(vec!["_"])
+                .http_timeout(std::time::Duration::from_millis(200))
+                .load_via(MessageLocatorLoadVia::Http))
+    ); // msg_locator
+    let error = msg_locator.try_load(None).await.unwrap_err();
+    assert!(matches!(error, MessageLoadError::Http { .. }));
+    assert!(msg_locator.current_locale().is_none());
+}
+
+#[tokio::test]
+async fn msg_locator_http_concurrent_load() {
+    use std::sync::{Arc, atomic::{AtomicUsize, Ordering}};
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let max_in_flight = Arc::new(AtomicUsize::new(0));
+    let url = {
+        let (in_flight, max_in_flight) = (in_flight.clone(), max_in_flight.clone());
+        serve_http_with(move |_, _| {
+            max_in_flight.fetch_max(in_flight.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            in_flight.fetch_sub(1, Ordering::SeqCst);
+            let body = r#"{ "title": "Title" }"#;
+            format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body)
+        })
+    };
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en", "en-US", "pt-BR"])
+            .default_locale("pt-BR")
+            .fallback("pt-BR", vec!["en-US"])
+            .fallback("en-US", vec!["en"])
+            .assets(MessageLocatorAssetOptions::new()
+                .src(url)
+                .base_file_names(vec!["_", "menu"])
+                .load_via(MessageLocatorLoadVia::Http))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get("menu.title"), "Title".to_string());
+    assert!(max_in_flight.load(Ordering::SeqCst) > 1);
+}
+
+#[test]
+fn msg_locator_load_blocking() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en", "en-US"])
+            .default_locale("en-US")
+            .fallback("en-US", vec!["en"])
+            .assets(MessageLocatorAssetOptions::new()
+                .src("tests/res/lang")
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load_blocking(None));
+    assert_eq!(msg_locator.get("_.message_id"), "Some message".to_string());
+    assert!(msg_locator.update_locale_blocking(parse_locale("en").unwrap()));
+    assert!(msg_locator.current_locale() == Some(parse_locale("en").unwrap()));
+
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en"])
+            .default_locale("en")
+            .assets(MessageLocatorAssetOptions::new()
+                .src("http://127.0.0.1:1")
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::Http))
+    ); // msg_locator
+    assert!(!msg_locator.load_blocking(None));
+
+    let loader = std::sync::Arc::new(RecordingLoader { requested: std::sync::Mutex::new(vec![]) });
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en"])
+            .default_locale("en")
+            .assets(MessageLocatorAssetOptions::new()
+                .base_file_names(vec!["_"])
+                .loader(loader.clone()))
+    ); // msg_locator
+    assert!(!msg_locator.load_blocking(None));
+    assert!(loader.requested.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn msg_locator_get_formatted_with_fallbacks() {
+    let src = temp_assets("with_fallbacks", &[
+        ("de/_.json", r#"{ "title": "Titel" }"#),
+        ("fr/_.json", r#"{ "title": "Titre", "terms": "Conditions de $name" }"#),
+        ("en/_.json", r#"{ "title": "Title", "terms": "Terms of $name" }"#),
+    ]);
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["de", "fr", "en"])
+            .default_locale("de")
+            .fallback("de", vec!["fr"])
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["_"])
+                .clean_unused(false)
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.preload(&[parse_locale("en").unwrap()]).await.is_ok());
+    assert!(msg_locator.load(None).await);
+    let (de, en) = (parse_locale("de").unwrap(), parse_locale("en").unwrap());
+    let vars = localization_vars!{ "name" => "Service" };
+    assert_eq!(msg_locator.get_formatted_with_fallbacks("_.terms", &[de.clone(), en.clone()], vec![&vars]), "Terms of Service".to_string());
+    assert_eq!(msg_locator.get_formatted_with_fallbacks("_.title", &[en.clone(), de.clone()], vec![]), "Title".to_string());
+    assert_eq!(msg_locator.get_formatted_with_fallbacks("_.terms", &[de], vec![]), "_.terms".to_string());
+    // the configured fallbacks still apply elsewhere
+    assert_eq!(msg_locator.get_formatted("_.terms", vec![&vars]), "Conditions de Service".to_string());
+}
+
+#[tokio::test]
+async fn msg_locator_reload() {
+    let src = temp_assets("reload", &[
+        ("en-US/_.json", r#"{ "title": "Draft" }"#),
+        ("en/_.json", r#"{ "subtitle": "Old" }"#),
+    ]);
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US", "en"])
+            .default_locale("en-US")
+            .fallback("en-US", vec!["en"])
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.reload().await);
+    assert!(msg_locator.load(None).await);
+    std::fs::write(format!("{}/en-US/_.json", src), r#"{ "title": "Final" }"#).unwrap();
+    std::fs::write(format!("{}/en/_.json", src), r#"{ "subtitle": "New" }"#).unwrap();
+    assert_eq!(msg_locator.get("_.title"), "Draft".to_string());
+    assert!(msg_locator.reload().await);
+    assert_eq!(msg_locator.get("_.title"), "Final".to_string());
+    assert_eq!(msg_locator.get("_.subtitle"), "New".to_string());
+
+    std::fs::write(format!("{}/en/_.json", src), "{").unwrap();
+    assert!(!msg_locator.reload().await);
+    assert_eq!(msg_locator.get("_.subtitle"), "New".to_string());
+}
+
+#[tokio::test]
+async fn msg_locator_validate_variables() {
+    let src = temp_assets("validate_variables", &[
+        ("en/_.json", r#"{
+            "greeting": "Hello, $name! You have $cuont items, $$5 each.",
+            "greeting@vars": ["name", "count"],
+            "farewell": "Bye, $name|upper",
+            "farewell@vars": ["name"],
+            "menu": { "open": "Open $file and $files", "open@vars": ["file"] },
+            "undeclared": "Hi $who"
+        }"#),
+    ]);
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en"])
+            .default_locale("en")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.validate_variables(&parse_locale("en").unwrap()), vec![
+        ("_.greeting".to_string(), "cuont".to_string()),
+        ("_.menu.open".to_string(), "files".to_string()),
+    ]);
+}
+
+#[tokio::test]
+async fn msg_locator_positional_variables() {
+    let src = temp_assets("positional", &[
+        ("en/_.json", r#"{
+            "greet": "Hi $1",
+            "transfer": "$1 sent $2 to $name",
+            "contextual_female": "She is $1",
+            "contextual_other": "They are $1"
+        }"#),
+    ]);
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en"])
+            .default_locale("en")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get_formatted("_.greet", vec![&"Alice"]), "Hi Alice".to_string());
+    let vars = localization_vars!{ "name" => "Bob" };
+    assert_eq!(msg_locator.get_formatted("_.transfer", vec![&"Alice", &5, &vars]), "Alice sent 5 to Bob".to_string());
+    assert_eq!(msg_locator.get_formatted("_.contextual", vec![&"female"]), "She is female".to_string());
+    assert_eq!(msg_locator.get_formatted("_.contextual", vec![&"male"]), "They are male".to_string());
+}
+
+#[test]
+fn msg_locator_percent_formatting() {
+    let msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en", "fr", "tr"])
+            .default_locale("en")
+    ); // msg_locator
+    let (en, fr, tr) = (parse_locale("en").unwrap(), parse_locale("fr").unwrap(), parse_locale("tr").unwrap());
+    let vars = localization_vars!{ "ratio" => "0.5", "rate" => "0.0125", "growth" => "12.346" };
+    assert_eq!(msg_locator.format_message(&en, "$ratio|percent", &vars), "50%".to_string());
+    assert_eq!(msg_locator.format_message(&fr, "$ratio|percent", &vars), "50\u{202F}%".to_string());
+    assert_eq!(msg_locator.format_message(&tr, "$ratio|percent", &vars), "%50".to_string());
+    assert_eq!(msg_locator.format_message(&en, "$rate|percent:2", &vars), "1.25%".to_string());
+    assert_eq!(msg_locator.format_message(&fr, "$rate|percent:2", &vars), "1,25\u{202F}%".to_string());
+    assert_eq!(msg_locator.format_message(&en, "$rate|permille:1", &vars), "12.5‰".to_string());
+    assert_eq!(msg_locator.format_message(&fr, "$rate|permille:1", &vars), "12,5\u{202F}‰".to_string());
+    assert_eq!(msg_locator.format_message(&en, "$growth|percent", &vars), "1,235%".to_string());
+    assert_eq!(msg_locator.format_message(&fr, "$growth|percent", &vars), "1\u{202F}235\u{202F}%".to_string());
+}
+
+#[test]
+fn msg_locator_variants_of() {
+    let msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US", "pt-PT", "en-GB", "ja", "pt-BR", "en-AU", "en"])
+            .default_locale("en-US")
+    ); // msg_locator
+    let tags = |language: &str| -> Vec<String> {
+        msg_locator.variants_of(language).iter().map(|l| l.standard_tag().to_string()).collect()
+    };
+    assert_eq!(tags("en"), vec!["en", "en-AU", "en-GB", "en-US"]);
+    assert_eq!(tags("PT"), vec!["pt-BR", "pt-PT"]);
+    assert!(tags("de").is_empty());
+    assert_eq!(parse_locale("pt-BR").unwrap().language(), "pt".to_string());
+}
+
+#[test]
+fn msg_locator_placeholder_defaults() {
+    let en = parse_locale("en").unwrap();
+    let vars = localization_vars!{ "name" => "Ana" };
+    let msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en"])
+            .default_locale("en")
+    ); // msg_locator
+    assert_eq!(msg_locator.format_message(&en, "Hi ${name:Guest}, ${title:dear reader}!", &vars), "Hi Ana, dear reader!".to_string());
+    assert_eq!(msg_locator.format_message(&en, "${name|upper} ${title} $title ${title:}.", &vars), "ANA undefined undefined .".to_string());
+    assert_eq!(msg_locator.format_message(&en, "$${name}", &vars), "${name}".to_string());
+
+    let msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en"])
+            .default_locale("en")
+            .placeholder_style(PlaceholderStyle::Braces)
+    ); // msg_locator
+    assert_eq!(msg_locator.format_message(&en, "Hi {name:Guest}, {title:dear reader}!", &vars), "Hi Ana, dear reader!".to_string());
+}
+
+#[test]
+fn locale_negotiate_locale() {
+    let supported: std::collections::HashSet<Locale> = vec!["en", "en-US", "pt-BR", "ja"].into_iter().map(|s| parse_locale(s).unwrap()).collect();
+    let default = parse_locale("ja").unwrap();
+    let negotiate = |header: &str| negotiate_locale(header, &supported, &default).standard_tag().to_string();
+    assert_eq!(negotiate("en-US,en;q=0.9,pt;q=0.8"), "en-US");
+    assert_eq!(negotiate("pt;q=0.8,en-GB;q=0.9"), "en");
+    assert_eq!(negotiate("pt"), "pt-BR");
+    assert_eq!(negotiate("de, fr;q=0.5, en;q=0"), "ja");
+    assert_eq!(negotiate("*, invalid-tag-!, pt-PT;q=0.1"), "pt-BR");
+    assert_eq!(negotiate(""), "ja");
+}
+
+#[tokio::test]
+async fn msg_locator_explain() {
+    let src = temp_assets("explain", &[
+        ("pt-BR/_.json", r#"{ "title": "Título" }"#),
+        ("pt-BR/menu.json", r#"{}"#),
+        ("en/_.json", r#"{ "title": "Title" }"#),
+        ("en/menu.json", r#"{ "open": "Open" }"#),
+    ]);
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["pt-BR", "en"])
+            .default_locale("pt-BR")
+            .fallback("pt-BR", vec!["en"])
+            .track_key_sources(true)
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["_", "menu"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    let trace = msg_locator.explain("menu.open");
+    assert_eq!(trace.steps.len(), 2);
+    assert!(!trace.steps[0].found);
+    assert!(trace.steps[1].found);
+    assert_eq!(trace.steps[1].source, Some("menu".to_string()));
+    assert_eq!(trace.message, Some("Open".to_string()));
+    assert_eq!(trace.to_string(), "menu.open:\n  pt-BR: not found\n  en: found in menu\n  => \"Open\"");
+    assert_eq!(msg_locator.explain("menu.close").to_string(), "menu.close:\n  pt-BR: not found\n  en: not found\n  => missing");
+}
+
+#[test]
+fn locale_system_locale() {
+    let env = |vars: HashMap<&'static str, &'static str>| move |name: &str| vars.get(name).map(|value| value.to_string());
+    assert!(locale_from_env(env(maplit::hashmap! { "LC_ALL" => "pt_BR.UTF-8@euro", "LANG" => "en_US" })) == Some(parse_locale("pt-BR").unwrap()));
+    assert!(locale_from_env(env(maplit::hashmap! { "LC_ALL" => "C", "LANG" => "en_US" })).is_none());
+    assert!(locale_from_env(env(maplit::hashmap! { "LC_ALL" => "", "LC_MESSAGES" => "ja_JP", "LANG" => "en_US" })) == Some(parse_locale("ja-JP").unwrap()));
+    assert!(locale_from_env(env(maplit::hashmap! { "LANG" => "xx" })).is_none());
+    assert!(locale_from_env(env(HashMap::new())).is_none());
+}
+
+#[tokio::test]
+async fn msg_locator_current_locale_seq_ordered() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["pt-BR", "pt", "es", "en"])
+            .default_locale("pt-BR")
+            .fallback("pt-BR", vec!["pt", "es"])
+            .fallback("pt", vec!["en"])
+            .fallback("es", vec!["en"])
+            .assets(MessageLocatorAssetOptions::new()
+                .base_file_names(Vec::<String>::new())
+                .load_via(MessageLocatorLoadVia::Memory))
+    ); // msg_locator
+    assert!(msg_locator.current_locale_seq_ordered().is_empty());
+    assert!(msg_locator.load(None).await);
+    let tags: Vec<String> = msg_locator.current_locale_seq_ordered().iter().map(|l| l.standard_tag().to_string()).collect();
+    assert_eq!(tags, vec!["pt-BR", "pt", "en", "es"]);
+    assert_eq!(msg_locator.current_locale_seq().len(), 4);
+}
+
+#[tokio::test]
+async fn msg_locator_get_with_source() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US", "en"])
+            .default_locale("en-US")
+            .fallback("en-US", vec!["en"])
+            .assets(MessageLocatorAssetOptions::new()
+                .base_file_names(vec!["_"])
+                .memory(maplit::hashmap! {
+                    "en-US/_".to_string() => serde_json::json!({ "color": "Color" }),
+                    "en/_".to_string() => serde_json::json!({ "color": "Colour", "greeting": "Hello, $name" }),
+                })
+                .load_via(MessageLocatorLoadVia::Memory))
+    ); // msg_locator
+    assert!(msg_locator.get_with_source("_.color", vec![]).is_none());
+    assert!(msg_locator.load(None).await);
+    let (message, source) = msg_locator.get_with_source("_.color", vec![]).unwrap();
+    assert_eq!(message, "Color".to_string());
+    assert!(source == parse_locale("en-US").unwrap());
+    let vars = localization_vars!{ "name" => "Ana" };
+    let (message, source) = msg_locator.get_with_source("_.greeting", vec![&vars]).unwrap();
+    assert_eq!(message, "Hello, Ana".to_string());
+    assert!(source == parse_locale("en").unwrap());
+    assert!(msg_locator.get_with_source("_.missing", vec![]).is_none());
+}
+
+#[test]
+fn msg_locator_bidi_isolation() {
+    let msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["ar", "en"])
+            .default_locale("ar")
+            .bidi_isolation(true)
+    ); // msg_locator
+    let (ar, en) = (parse_locale("ar").unwrap(), parse_locale("en").unwrap());
+    let vars = localization_vars!{ "name" => "Ana", "total" => "5" };
+    assert_eq!(msg_locator.format_message(&ar, "مرحبا $name! $$$total", &vars), "مرحبا \u{2068}Ana\u{2069}! $\u{2068}5\u{2069}".to_string());
+    assert_eq!(msg_locator.format_message(&en, "Hi $name! $$$total", &vars), "Hi Ana! $5".to_string());
+}
+
+#[tokio::test]
+async fn msg_locator_on_missing() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en"])
+            .default_locale("en")
+            .on_missing(|id| format!("⟪{}⟫", id))
+            .assets(MessageLocatorAssetOptions::new()
+                .base_file_names(vec!["_"])
+                .memory(maplit::hashmap! {
+                    "en/_".to_string() => serde_json::json!({ "title": "Title", "files": { "other": "$count files" } }),
+                })
+                .load_via(MessageLocatorLoadVia::Memory))
+    ); // msg_locator
+    assert_eq!(msg_locator.get("_.title"), "⟪_.title⟫".to_string());
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get("_.title"), "Title".to_string());
+    assert_eq!(msg_locator.get("_.missing"), "⟪_.missing⟫".to_string());
+    assert_eq!(msg_locator.get_formatted("_.contextual", vec![&"male"]), "⟪_.contextual_male⟫".to_string());
+    assert_eq!(msg_locator.get_plural("_.files", 2, vec![]), "2 files".to_string());
+    assert_eq!(msg_locator.get_plural("_.folders", 2, vec![]), "⟪_.folders⟫".to_string());
+    assert!(msg_locator.get_opt("_.missing", vec![]).is_none());
+}
+
+#[tokio::test]
+async fn msg_locator_id_separator() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en"])
+            .default_locale("en")
+            .id_separator(':')
+            .assets(MessageLocatorAssetOptions::new()
+                .base_file_names(vec!["_"])
+                .memory(maplit::hashmap! {
+                    "en/_".to_string() => serde_json::json!({
+                        "example.com": { "title": "Example" },
+                        "files": { "one": "$count file", "other": "$count files" },
+                    }),
+                })
+                .load_via(MessageLocatorLoadVia::Memory))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get("_:example.com:title"), "Example".to_string());
+    assert_eq!(msg_locator.get_plural("_:files", 1, vec![]), "1 file".to_string());
+    assert!(msg_locator.message_keys(&parse_locale("en").unwrap()).contains(&"_:example.com:title".to_string()));
+    assert_eq!(msg_locator.get("_.example.com.title"), "_.example.com.title".to_string());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn locale_serde() {
+    let locale = parse_locale("pt_BR").unwrap();
+    assert_eq!(serde_json::to_string(&locale).unwrap(), r#""pt-BR""#.to_string());
+    assert!(serde_json::from_str::<Locale>(r#""pt-BR""#).unwrap() == locale);
+    assert!(serde_json::from_str::<Locale>(r#""not a locale""#).is_err());
+    let country = parse_country("BRA").unwrap();
+    assert_eq!(serde_json::to_string(&country).unwrap(), r#""BR""#.to_string());
+    assert!(serde_json::from_str::<Country>(r#""BR""#).unwrap() == country);
+    assert!(serde_json::from_str::<Country>(r#""ZZ""#).is_err());
+}
+
+#[test]
+fn msg_locator_match_supported() {
+    let msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US", "en", "en-GB", "pt-BR", "zh-Hant"])
+            .default_locale("en-US")
+    ); // msg_locator
+    assert!(msg_locator.match_supported("EN_us") == Some(parse_locale("en-US").unwrap()));
+    assert!(msg_locator.match_supported("en_gb") == Some(parse_locale("en-GB").unwrap()));
+    assert!(msg_locator.match_supported("ZH-hant") == Some(parse_locale("zh-Hant").unwrap()));
+    assert!(msg_locator.match_supported("en-AU") == Some(parse_locale("en").unwrap()));
+    assert!(msg_locator.match_supported("PT") == Some(parse_locale("pt-BR").unwrap()));
+    assert!(msg_locator.match_supported("ja-JP").is_none());
+    assert!(msg_locator.match_supported("not a locale").is_none());
+    assert!(!msg_locator.supports_locale(&parse_locale("en-AU").unwrap()));
+}
+
+#[tokio::test]
+async fn msg_locator_load_unsupported() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en"])
+            .default_locale("en")
+            .assets(MessageLocatorAssetOptions::new()
+                .base_file_names(vec!["_"])
+                .memory(maplit::hashmap! {
+                    "en/_".to_string() => serde_json::json!({ "title": "Title" }),
+                })
+                .load_via(MessageLocatorLoadVia::Memory))
+    ); // msg_locator
+    let ja = parse_locale("ja").unwrap();
+    let error = msg_locator.try_load(Some(ja.clone())).await.unwrap_err();
+    assert!(matches!(error, MessageLoadError::Unsupported { .. }));
+    assert!(!msg_locator.update_locale(ja.clone()).await);
+    assert!(matches!(msg_locator.preload(&[ja]).await, Err(MessageLoadError::Unsupported { .. })));
+    assert!(msg_locator.current_locale().is_none());
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get("_.title"), "Title".to_string());
+}
+
+struct TableLoader {
+    rows: HashMap<(String, String), serde_json::Value>,
+}
+
+#[cfg_attr(not(feature = "sync"), async_trait::async_trait(?Send))]
+#[cfg_attr(feature = "sync", async_trait::async_trait)]
+impl AssetLoader for TableLoader {
+    async fn load(&self, locale_path: &str, base_name: &str) -> Result<serde_json::Value, MessageLoadError> {
+        self.rows.get(&(locale_path.to_string(), base_name.to_string())).cloned().ok_or_else(|| MessageLoadError::Io {
+            path: format!("{}/{}", locale_path, base_name),
+            source: std::io::Error::new(std::io::ErrorKind::NotFound, "no row"),
+        })
+    }
+}
+
+#[tokio::test]
+async fn msg_locator_asset_loader() {
+    let loader = std::sync::Arc::new(TableLoader {
+        rows: maplit::hashmap! {
+            ("en".to_string(), "_".to_string()) => serde_json::json!({ "title": "Title" }),
+            ("".to_string(), "brand".to_string()) => serde_json::json!({ "name": "Recoyx" }),
+        },
+    });
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en", "pt"])
+            .default_locale("en")
+            .assets(MessageLocatorAssetOptions::new()
+                .base_file_names(vec!["_"])
+                .shared_base_files(vec!["brand"], "shared")
+                .loader(loader))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get("_.title"), "Title".to_string());
+    assert_eq!(msg_locator.get("brand.name"), "Recoyx".to_string());
+    let error = msg_locator.try_load(Some(parse_locale("pt").unwrap())).await.unwrap_err();
+    assert_eq!(error.path(), Some("pt/_"));
+}
+
+#[tokio::test]
+async fn msg_locator_http_retries() {
+    let new_locator = |src: &str| MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en"])
+            .default_locale("en")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(src)
+                .base_file_names(vec!["_"])
+                .http_retries(3)
+                .http_retry_backoff(std::time::Duration::from_millis(1))
+                .load_via(MessageLocatorLoadVia::Http))
+    ); // new_locator
+    let (src, requests) = serve_flaky_http(r#"{"title": "Title"}"#, 2, "503 Service Unavailable");
+    let mut msg_locator = new_locator(&src);
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get("_.title"), "Title".to_string());
+    assert_eq!(requests.load(std::sync::atomic::Ordering::SeqCst), 3);
+
+    let (src, requests) = serve_flaky_http(r#"{"title": "Title"}"#, 1, "404 Not Found");
+    assert!(!new_locator(&src).load(None).await);
+    assert_eq!(requests.load(std::sync::atomic::Ordering::SeqCst), 1);
+}
+
+#[test]
+fn msg_locator_recursive_interpolation() {
+    let new_locator = |recursive: bool| MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en"])
+            .default_locale("en")
+            .recursive_interpolation(recursive)
+    ); // new_locator
+    let en = parse_locale("en").unwrap();
+    let vars = localization_vars!{ "greeting" => "Hi $name", "name" => "Ana", "a" => "[$b]", "b" => "($a)" };
+    assert_eq!(new_locator(false).format_message(&en, "$greeting!", &vars), "Hi $name!".to_string());
+    assert_eq!(new_locator(true).format_message(&en, "$greeting|upper!", &vars), "HI ANA!".to_string());
+    // cycles stop at the nesting limit
+    assert_eq!(new_locator(true).format_message(&en, "$a", &vars), "[([([([([$b])])])])]".to_string());
+}
+
+#[test]
+fn msg_locator_recursive_interpolation_steps() {
+    let msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en"])
+            .default_locale("en")
+            .recursive_interpolation(true)
+            .max_format_steps(10)
+    ); // msg_locator
+    let en = parse_locale("en").unwrap();
+    // each level doubles the placeholders, which would expand to 256 leaves
+    let vars = localization_vars!{
+        "a" => "$b$b", "b" => "$c$c", "c" => "$d$d", "d" => "$e$e",
+        "e" => "$f$f", "f" => "$g$g", "g" => "$h$h", "h" => "$x$x", "x" => "x",
+    };
+    let r = msg_locator.format_message(&en, "$a", &vars);
+    assert_eq!(r.matches('x').count(), 2);
+    assert!(r.contains('$'));
+}
+
+#[tokio::test]
+async fn msg_locator_http_cache_dir() {
+    let cache_dir = std::env::temp_dir().join(format!("recoyx_message_localization_http_cache_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&cache_dir);
+    let new_locator = |src: &str| MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en"])
+            .default_locale("en")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(src)
+                .base_file_names(vec!["_"])
+                .http_cache_dir(cache_dir.clone())
+                .load_via(MessageLocatorLoadVia::Http))
+    ); // new_locator
+    let (offline, _) = serve_flaky_http("", usize::MAX, "503 Service Unavailable");
+    assert!(!new_locator(&offline).load(None).await);
+
+    let online = serve_http(vec![("en/_.json", r#"{"title": "Title"}"#.to_string())]);
+    assert!(new_locator(&online).load(None).await);
+    assert!(cache_dir.join("en/_.json").exists());
+
+    let mut msg_locator = new_locator(&offline);
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get("_.title"), "Title".to_string());
+}
+
+#[cfg(all(feature = "sync", not(feature = "wasm")))]
+#[tokio::test]
+async fn shared_msg_locator_auto_reload() {
+    let files = std::sync::Arc::new(std::sync::Mutex::new(maplit::hashmap! {
+        "en/_.json".to_string() => r#"{"title": "Draft"}"#.to_string(),
+    }));
+    let src = serve_shared_http(files.clone());
+    let new_locator = |interval: Option<std::time::Duration>| {
+        let assets = MessageLocatorAssetOptions::new();
+        assets.src(&src).base_file_names(vec!["_"]).load_via(MessageLocatorLoadVia::Http);
+        if let Some(interval) = interval {
+            assets.reload_interval(interval);
+        }
+        MessageLocator::new(MessageLocatorOptions::new().supported_locales(vec!["en"]).default_locale("en").assets(&assets))
+    }; // new_locator
+    assert!(SharedMessageLocator::new(new_locator(None)).spawn_auto_reload().is_none());
+
+    let mut msg_locator = new_locator(Some(std::time::Duration::from_millis(20)));
+    assert!(msg_locator.load(None).await);
+    let shared = SharedMessageLocator::new(msg_locator);
+    let reloads = shared.spawn_auto_reload().unwrap();
+    files.lock().unwrap().insert("en/_.json".to_string(), r#"{"title": "Final"}"#.to_string());
+    for _ in 0..100 {
+        if shared.get("_.title") == "Final" {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert_eq!(shared.get("_.title"), "Final".to_string());
+
+    // failed reloads keep the assets
+    files.lock().unwrap().clear();
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    assert_eq!(shared.get("_.title"), "Final".to_string());
+    reloads.abort();
+}
+
+#[test]
+fn msg_locator_options_build() {
+    assert!(MessageLocatorOptions::new().supported_locales(vec!["en", "pt-BR"]).default_locale("en").build().is_ok());
+    let error = MessageLocatorOptions::new().supported_locales(vec!["en", "xx-YY"]).default_locale("en").build().err().unwrap();
+    assert!(matches!(error, ConfigError::InvalidLocale { setting: "supported_locales", ref locale, .. } if locale == "xx-YY"));
+    let error = MessageLocatorOptions::new().default_locale("qq").build().err().unwrap();
+    assert!(matches!(error, ConfigError::InvalidLocale { setting: "default_locale", .. }));
+    let error = MessageLocatorOptions::new()
+        .assets(MessageLocatorAssetOptions::new().http_headers(maplit::hashmap! { "Bad Name".to_string() => "x".to_string() }))
+        .build().err().unwrap();
+    assert!(matches!(error, ConfigError::InvalidHttpHeader { .. }));
+}
+
+#[test]
+fn msg_locator_unsupported_fallbacks() {
+    let error = MessageLocatorOptions::new()
+        .supported_locales(vec!["en", "pt-BR"])
+        .default_locale("en")
+        .fallbacks(maplit::hashmap! { "pt-BR" => vec!["pt", "en", "es"] })
+        .build().err().unwrap();
+    assert!(matches!(error, ConfigError::UnsupportedFallbacks { ref entries } if *entries == vec![
+        ("pt-BR".to_string(), "es".to_string()),
+        ("pt-BR".to_string(), "pt".to_string()),
+    ]));
+    assert_eq!(error.to_string(), "Fallback locales are not supported locales: pt-BR -> es, pt-BR -> pt");
+}
+
+#[tokio::test]
+async fn msg_locator_merge_strategy() {
+    let new_locator = |strategy: MergeStrategy| MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en"])
+            .default_locale("en")
+            .track_key_sources(true)
+            .assets(MessageLocatorAssetOptions::new()
+                .base_file_names(vec!["common"])
+                .shared_base_files(vec!["common"], "shared")
+                .merge_strategy(strategy)
+                .memory(maplit::hashmap! {
+                    "common".to_string() => serde_json::json!({ "brand": "Recoyx", "menu": { "file": "File", "edit": "Edit" } }),
+                    "en/common".to_string() => serde_json::json!({ "title": "Title", "menu": { "edit": "Modify" } }),
+                })
+                .load_via(MessageLocatorLoadVia::Memory))
+    ); // new_locator
+    let en = parse_locale("en").unwrap();
+    let mut msg_locator = new_locator(MergeStrategy::Overwrite);
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get("common.brand"), "common.brand".to_string());
+    assert_eq!(msg_locator.get("common.menu.edit"), "Modify".to_string());
+
+    let mut msg_locator = new_locator(MergeStrategy::KeepExisting);
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get("common.title"), "common.title".to_string());
+    assert_eq!(msg_locator.get("common.menu.edit"), "Edit".to_string());
+
+    let mut msg_locator = new_locator(MergeStrategy::DeepMerge);
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get("common.brand"), "Recoyx".to_string());
+    assert_eq!(msg_locator.get("common.title"), "Title".to_string());
+    assert_eq!(msg_locator.get("common.menu.file"), "File".to_string());
+    assert_eq!(msg_locator.get("common.menu.edit"), "Modify".to_string());
+    assert_eq!(msg_locator.key_source(&en, "common.menu.file"), Some("common".to_string()));
+}
+
+#[test]
+fn msg_locator_select() {
+    let new_locator = |style: PlaceholderStyle| MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en"])
+            .default_locale("en")
+            .placeholder_style(style)
+    ); // new_locator
+    let en = parse_locale("en").unwrap();
+    let msg_locator = new_locator(PlaceholderStyle::Dollar);
+    let message = "{gender, select, male {He} female {She} other {They}} replied to $name{count, select, 0 {} other { and {gender, select, female {her} other {their}} friends}}.";
+    let format = |vars: HashMap<String, String>| msg_locator.format_message(&en, message, &vars);
+    assert_eq!(format(localization_vars!{ "gender" => "female", "name" => "Ana", "count" => "2" }), "She replied to Ana and her friends.".to_string());
+    assert_eq!(format(localization_vars!{ "gender" => "male", "name" => "Ana", "count" => "0" }), "He replied to Ana.".to_string());
+    assert_eq!(format(localization_vars!{ "name" => "Ana", "count" => "2" }), "They replied to Ana and their friends.".to_string());
+    assert_eq!(msg_locator.format_message(&en, "{n, plural, one {# item} other {# items}}", &HashMap::new()), "{n, plural, one {# item} other {# items}}".to_string());
+
+    let msg_locator = new_locator(PlaceholderStyle::Braces);
+    let vars = localization_vars!{ "gender" => "male", "name" => "Ana" };
+    assert_eq!(msg_locator.format_message(&en, "{gender, select, male {He thanked {name}} other {They thanked {name}}} {{ok}}", &vars), "He thanked Ana {ok}".to_string());
+}
+
+#[tokio::test]
+async fn msg_locator_single_file() {
+    let src = temp_assets("single_file", &[
+        ("en-US.json", r#"{"common": {"title": "Title"}, "greeting": "Hi $name"}"#),
+    ]);
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en-US"])
+            .default_locale("en-US")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["ignored"])
+                .single_file(true)
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get("common.title"), "Title".to_string());
+    assert_eq!(msg_locator.get_formatted("greeting", vec![&localization_vars!{ "name" => "Ana" }]), "Hi Ana".to_string());
+}
+
+#[test]
+fn all_locales_and_countries() {
+    let locales = all_locales();
+    assert!(locales.len() > 100);
+    let pt = locales.iter().find(|locale| locale.standard_tag().to_string() == "pt").unwrap();
+    assert_eq!(pt.to_string(), "Português".to_string());
+    assert!(locales.iter().all(|locale| !locale.native_name().is_empty()));
+    let countries = all_countries();
+    assert!(countries.iter().any(|country| country.standard_code().alpha2() == "BR"));
+    assert_eq!(countries.len(), 249);
+}
+
+#[test]
+fn locale_display_name_in() {
+    let (pt_br, en_us, ja) = (parse_locale("pt-BR").unwrap(), parse_locale("en-US").unwrap(), parse_locale("ja").unwrap());
+    assert_eq!(pt_br.display_name_in(&en_us), "Portuguese (Brazil)".to_string());
+    assert_eq!(parse_locale("de").unwrap().display_name_in(&en_us), "German".to_string());
+    assert_eq!(pt_br.display_name_in(&pt_br), pt_br.to_string());
+    assert_eq!(pt_br.display_name_in(&ja), pt_br.to_string());
+}
+
+#[tokio::test]
+async fn msg_locator_gzip_compression() {
+    use std::io::Write;
+    let src = temp_assets("gzip", &[]);
+    std::fs::create_dir_all(format!("{}/en", src)).unwrap();
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(r#"{"title": "Title"}"#.as_bytes()).unwrap();
+    std::fs::write(format!("{}/en/_.json.gz", src), encoder.finish().unwrap()).unwrap();
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en"])
+            .default_locale("en")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["_"])
+                .compression(Compression::Gzip)
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.preflight().is_empty());
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get("_.title"), "Title".to_string());
+}
+
+#[tokio::test]
+async fn msg_locator_aliases() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en", "zh-Hans", "id"])
+            .default_locale("en")
+            .aliases(maplit::hashmap! { "zh-CN" => "zh-Hans", "in" => "id" })
+            .assets(MessageLocatorAssetOptions::new()
+                .base_file_names(vec!["_"])
+                .memory(maplit::hashmap! {
+                    "en/_".to_string() => serde_json::json!({ "title": "Title" }),
+                    "zh-Hans/_".to_string() => serde_json::json!({ "title": "标题" }),
+                })
+                .load_via(MessageLocatorLoadVia::Memory))
+    ); // msg_locator
+    let zh_cn = parse_locale("zh-CN").unwrap();
+    assert!(msg_locator.supports_locale(&zh_cn));
+    assert!(msg_locator.match_supported("in") == Some(parse_locale("id").unwrap()));
+    assert!(msg_locator.match_supported("ZH_cn") == Some(parse_locale("zh-Hans").unwrap()));
+    assert!(msg_locator.update_locale(zh_cn).await);
+    assert!(msg_locator.current_locale() == Some(parse_locale("zh-Hans").unwrap()));
+    assert_eq!(msg_locator.get("_.title"), "标题".to_string());
+}
+
+#[tokio::test]
+async fn msg_locator_localized_number() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en", "pt-BR"])
+            .default_locale("en")
+            .assets(MessageLocatorAssetOptions::new()
+                .base_file_names(vec!["_"])
+                .memory(maplit::hashmap! {
+                    "en/_".to_string() => serde_json::json!({ "total": "Total: $1" }),
+                    "pt-BR/_".to_string() => serde_json::json!({ "total": "Total: $1", "summary": "$name: $count itens" }),
+                })
+                .load_via(MessageLocatorLoadVia::Memory))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get_formatted("_.total", vec![&LocalizedNumber(1000000.0)]), "Total: 1,000,000".to_string());
+    assert!(msg_locator.update_locale(parse_locale("pt-BR").unwrap()).await);
+    assert_eq!(msg_locator.get_formatted("_.total", vec![&LocalizedNumber(-1234.5)]), "Total: -1.234,5".to_string());
+    let vars = localization_vars!{ "name" => "Ana" };
+    assert_eq!(msg_locator.get_formatted("_.summary", vec![&vars, &NumberArg::new("count", 1500.0)]), "Ana: 1.500 itens".to_string());
+}
+
+#[tokio::test]
+async fn msg_locator_coverage_report() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en", "pt-BR", "es"])
+            .default_locale("en")
+            .assets(MessageLocatorAssetOptions::new()
+                .base_file_names(vec!["_"])
+                .memory(maplit::hashmap! {
+                    "en/_".to_string() => serde_json::json!({ "a": "A", "b": { "c": "C" }, "d": "D" }),
+                    "pt-BR/_".to_string() => serde_json::json!({ "a": "A", "b": { "c": 1 } }),
+                    "es/_".to_string() => serde_json::json!({ "a": "A", "b": { "c": "C" }, "d": "D" }),
+                })
+                .load_via(MessageLocatorLoadVia::Memory))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert!(msg_locator.preload(&[parse_locale("pt-BR").unwrap(), parse_locale("es").unwrap()]).await.is_ok());
+    let report = msg_locator.coverage_report();
+    assert_eq!(report.len(), 2);
+    assert_eq!(report[&parse_locale("pt-BR").unwrap()], vec!["_.b.c".to_string(), "_.d".to_string()]);
+    assert!(report[&parse_locale("es").unwrap()].is_empty());
+}
+
+#[tokio::test]
+async fn msg_locator_backslash_escapes() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en"])
+            .default_locale("en")
+            .assets(MessageLocatorAssetOptions::new()
+                .base_file_names(vec!["_"])
+                .memory(maplit::hashmap! {
+                    "en/_".to_string() => serde_json::json!({
+                        "literal": r"Use \$name for $name, \\ for a backslash, $$ for a dollar.",
+                        "path": r"C:\\$dir",
+                    }),
+                })
+                .load_via(MessageLocatorLoadVia::Memory))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    assert_eq!(msg_locator.get_formatted("_.literal", vec![&maplit::hashmap! { "name".to_string() => "names".to_string() }]), r"Use $name for names, \ for a backslash, $ for a dollar.".to_string());
+    assert_eq!(msg_locator.get_formatted("_.path", vec![&maplit::hashmap! { "dir".to_string() => "Users".to_string() }]), r"C:\Users".to_string());
+}
+
+#[tokio::test]
+async fn msg_locator_load_namespaces() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en", "pt-BR"])
+            .default_locale("en")
+            .fallbacks(maplit::hashmap! { "pt-BR" => vec!["en"] })
+            .assets(MessageLocatorAssetOptions::new()
+                .base_file_names(vec!["auth", "inbox"])
+                .memory(maplit::hashmap! {
+                    "en/auth".to_string() => serde_json::json!({ "login": "Log in", "logout": "Log out" }),
+                    "en/inbox".to_string() => serde_json::json!({ "title": "Inbox" }),
+                    "pt-BR/auth".to_string() => serde_json::json!({ "login": "Entrar" }),
+                    "pt-BR/inbox".to_string() => serde_json::json!({ "title": "Caixa de entrada" }),
+                })
+                .load_via(MessageLocatorLoadVia::Memory))
+    ); // msg_locator
+    assert!(msg_locator.load_namespaces(parse_locale("pt-BR").ok(), &["auth".to_string()]).await);
+    assert_eq!(msg_locator.get("auth.login"), "Entrar".to_string());
+    assert_eq!(msg_locator.get("auth.logout"), "Log out".to_string());
+    assert_eq!(msg_locator.get("inbox.title"), "inbox.title".to_string());
+    assert!(msg_locator.load_namespaces(parse_locale("pt-BR").ok(), &["inbox".to_string()]).await);
+    assert_eq!(msg_locator.get("auth.login"), "Entrar".to_string());
+    assert_eq!(msg_locator.get("inbox.title"), "Caixa de entrada".to_string());
+    assert!(!msg_locator.load_namespaces(parse_locale("pt-BR").ok(), &["missing".to_string()]).await);
+}
+
+struct RecordingLoader {
+    requested: std::sync::Mutex<Vec<String>>,
+}
+
+#[cfg_attr(not(feature = "sync"), async_trait::async_trait(?Send))]
+#[cfg_attr(feature = "sync", async_trait::async_trait)]
+impl AssetLoader for RecordingLoader {
+    async fn load(&self, locale_path: &str, _base_name: &str) -> Result<serde_json::Value, MessageLoadError> {
+        self.requested.lock().unwrap().push(locale_path.to_string());
+        Ok(serde_json::json!({}))
+    }
+}
+
+#[tokio::test]
+async fn msg_locator_get_in_loading() {
+    let src = temp_assets("get_in_loading", &[
+        ("en/_.json", r#"{ "hello": "Hello" }"#),
+        ("ja/_.json", r#"{ "hello": "こんにちは" }"#),
+    ]);
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en", "ja"])
+            .default_locale("en")
+            .assets(MessageLocatorAssetOptions::new()
+                .src(&src)
+                .base_file_names(vec!["_"])
+                .load_via(MessageLocatorLoadVia::FileSystem))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    let ja = parse_locale("ja").unwrap();
+    assert_eq!(msg_locator.get_in(&ja, "_.hello", vec![]), "_.hello".to_string());
+    assert_eq!(msg_locator.get_in_loading(&ja, "_.hello", vec![]).await, "こんにちは".to_string());
+    assert_eq!(msg_locator.get_in(&ja, "_.hello", vec![]), "こんにちは".to_string());
+    assert!(msg_locator.current_locale().unwrap() == parse_locale("en").unwrap());
+
+    // locales that are already loaded do not hit the loader again
+    let loader = std::sync::Arc::new(RecordingLoader { requested: std::sync::Mutex::new(vec![]) });
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en", "ja"])
+            .default_locale("en")
+            .assets(MessageLocatorAssetOptions::new()
+                .base_file_names(vec!["_"])
+                .loader(loader.clone()))
+    ); // msg_locator
+    assert!(msg_locator.preload(&[parse_locale("en").unwrap()]).await.is_ok());
+    msg_locator.get_in_loading(&parse_locale("en").unwrap(), "_.hello", vec![]).await;
+    msg_locator.get_in_loading(&ja, "_.hello", vec![]).await;
+    msg_locator.get_in_loading(&ja, "_.hello", vec![]).await;
+    assert_eq!(*loader.requested.lock().unwrap(), vec!["en", "ja"]);
+}
+
+#[tokio::test]
+async fn msg_locator_load_order() {
+    let loader = std::sync::Arc::new(RecordingLoader { requested: std::sync::Mutex::new(vec![]) });
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en", "en-GB", "pt", "pt-BR", "es"])
+            .default_locale("en")
+            .fallbacks(maplit::hashmap! {
+                "pt-BR" => vec!["pt", "es"],
+                "pt" => vec!["en-GB"],
+                "en-GB" => vec!["en"],
+                "es" => vec!["en"],
+            })
+            .assets(MessageLocatorAssetOptions::new()
+                .base_file_names(vec!["_"])
+                .loader(loader.clone()))
+    ); // msg_locator
+    assert!(msg_locator.load(parse_locale("pt-BR").ok()).await);
+    assert_eq!(*loader.requested.lock().unwrap(), vec!["pt-BR", "pt", "en-GB", "en", "es"]);
+    let seq: Vec<String> = msg_locator.current_locale_seq_ordered().iter().map(|l| l.standard_tag().to_string()).collect();
+    assert_eq!(seq, vec!["pt-BR", "pt", "en-GB", "en", "es"]);
+}
+
+#[tokio::test]
+async fn shared_msg_locator() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en", "pt-BR"])
+            .default_locale("en")
+            .assets(MessageLocatorAssetOptions::new()
+                .base_file_names(vec!["_"])
+                .memory(maplit::hashmap! {
+                    "en/_".to_string() => serde_json::json!({ "title": "Inbox" }),
+                    "pt-BR/_".to_string() => serde_json::json!({ "title": "Caixa de entrada" }),
+                })
+                .load_via(MessageLocatorLoadVia::Memory))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    let shared = SharedMessageLocator::new(msg_locator);
+    let widget = shared.clone();
+    let changes = widget.subscribe();
+    assert_eq!(widget.get("_.title"), "Inbox".to_string());
+    assert!(shared.update_locale(parse_locale("pt-BR").unwrap()).await);
+    assert!(changes.try_recv().unwrap() == parse_locale("pt-BR").unwrap());
+    assert_eq!(widget.get("_.title"), "Caixa de entrada".to_string());
+    assert!(!shared.update_locale(parse_locale("fr").unwrap()).await);
+    assert!(changes.try_recv().is_err());
+    assert_eq!(widget.get("_.title"), "Caixa de entrada".to_string());
+}
+
+/// Loads `{ "title": <locale path> }`, taking longer for `pt-BR`.
+struct SlowLoader;
+
+#[cfg_attr(not(feature = "sync"), async_trait::async_trait(?Send))]
+#[cfg_attr(feature = "sync", async_trait::async_trait)]
+impl AssetLoader for SlowLoader {
+    async fn load(&self, locale_path: &str, _base_name: &str) -> Result<serde_json::Value, MessageLoadError> {
+        if locale_path == "pt-BR" {
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        Ok(serde_json::json!({ "title": locale_path }))
+    }
+}
+
+#[tokio::test]
+async fn shared_msg_locator_concurrent_updates() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en", "pt-BR"])
+            .default_locale("en")
+            .assets(MessageLocatorAssetOptions::new()
+                .base_file_names(vec!["_"])
+                .loader(std::sync::Arc::new(SlowLoader)))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    let shared = SharedMessageLocator::new(msg_locator);
+    let changes = shared.subscribe();
+    let (slow, fast) = tokio::join!(
+        shared.update_locale(parse_locale("pt-BR").unwrap()),
+        shared.update_locale(parse_locale("en").unwrap()),
+    );
+    assert!(slow && fast);
+    assert_eq!(shared.get("_.title"), "en".to_string());
+    let received: Vec<String> = changes.try_iter().map(|l| l.standard_tag().to_string()).collect();
+    assert_eq!(received, vec!["pt-BR", "en"]);
+}
+
+#[tokio::test]
+async fn msg_locator_get_in() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en", "pt-BR"])
+            .default_locale("en")
+            .fallbacks(maplit::hashmap! { "pt-BR" => vec!["en"] })
+            .assets(MessageLocatorAssetOptions::new()
+                .base_file_names(vec!["_"])
+                .memory(maplit::hashmap! {
+                    "en/_".to_string() => serde_json::json!({ "subject": "Hello, $1", "footer": "Bye" }),
+                    "pt-BR/_".to_string() => serde_json::json!({ "subject": "Olá, $1" }),
+                })
+                .load_via(MessageLocatorLoadVia::Memory))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    let pt_br = parse_locale("pt-BR").unwrap();
+    // only the fallback is loaded
+    assert_eq!(msg_locator.get_in(&pt_br, "_.subject", vec![&"Ana"]), "Hello, Ana".to_string());
+    assert!(msg_locator.preload(std::slice::from_ref(&pt_br)).await.is_ok());
+    assert_eq!(msg_locator.get_in(&pt_br, "_.subject", vec![&LocalizedNumber(1500.0)]), "Olá, 1.500".to_string());
+    assert_eq!(msg_locator.get_in(&pt_br, "_.footer", vec![]), "Bye".to_string());
+    assert!(msg_locator.current_locale().unwrap() == parse_locale("en").unwrap());
+    assert_eq!(msg_locator.get_formatted("_.subject", vec![&LocalizedNumber(1500.0)]), "Hello, 1,500".to_string());
+}
+
+#[tokio::test]
+async fn msg_locator_number_blocks() {
+    let mut msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en", "pt-BR"])
+            .default_locale("en")
+            .assets(MessageLocatorAssetOptions::new()
+                .base_file_names(vec!["_"])
+                .memory(maplit::hashmap! {
+                    "en/_".to_string() => serde_json::json!({ "stats": "{count, number} items, {ratio, number, percent} done, $${price, number, .2}, {other, number, fancy}" }),
+                    "pt-BR/_".to_string() => serde_json::json!({ "stats": "{count, number} itens, {ratio, number, percent} feito, R$${price, number, .2}" }),
+                })
+                .load_via(MessageLocatorLoadVia::Memory))
+    ); // msg_locator
+    assert!(msg_locator.load(None).await);
+    let vars = maplit::hashmap! {
+        "count".to_string() => "12500".to_string(),
+        "ratio".to_string() => "0.25".to_string(),
+        "price".to_string() => "1999.5".to_string(),
+        "other".to_string() => "1".to_string(),
+    };
+    assert_eq!(msg_locator.get_formatted("_.stats", vec![&vars]), "12,500 items, 25% done, $1,999.50, {other, number, fancy}".to_string());
+    let mut unparsed = vars.clone();
+    unparsed.insert("count".to_string(), "many".to_string());
+    assert!(msg_locator.update_locale(parse_locale("pt-BR").unwrap()).await);
+    assert_eq!(msg_locator.get_formatted("_.stats", vec![&unparsed]), "many itens, 25% feito, R$1.999,50".to_string());
+}
+
+#[test]
+fn locale_parse_normalizes_case_and_whitespace() {
+    assert_eq!(parse_locale(" En-us ").unwrap().standard_tag().to_string(), "en-US");
+    assert_eq!(parse_locale("ZH_hant_tw").unwrap().standard_tag().to_string(), "zh-Hant-TW");
+    assert_eq!(parse_locale("\tPT-br\n").unwrap().standard_tag().to_string(), "pt-BR");
+    assert!(parse_locale(" en-US ").unwrap() == parse_locale("en-US").unwrap());
+    assert_eq!(parse_locale("qq-US").err().unwrap(), "Unknown language in locale code qq-US.".to_string());
+}
+
+#[test]
+fn msg_locator_nested_select_plural() {
+    let msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en", "ru"])
+            .default_locale("en")
+    ); // msg_locator
+    let en = parse_locale("en").unwrap();
+    let message = "{gender, select, male {{count, plural, =0 {$name has no items} one {$name has # item} other {$name has # items}}} \
+        other {{count, plural, one {$name has # item{extra, select, yes { and {bonus, plural, one {# bonus} other {# bonuses}}} other {}}} other {$name has # items}}}}.";
+    let format = |vars: HashMap<String, String>| msg_locator.format_message(&en, message, &vars);
+    assert_eq!(format(localization_vars!{ "gender" => "male", "count" => "0", "name" => "Ivo" }), "Ivo has no items.".to_string());
+    assert_eq!(format(localization_vars!{ "gender" => "male", "count" => "1", "name" => "Ivo" }), "Ivo has 1 item.".to_string());
+    assert_eq!(format(localization_vars!{ "gender" => "male", "count" => "1200", "name" => "Ivo" }), "Ivo has 1,200 items.".to_string());
+    assert_eq!(format(localization_vars!{ "count" => "1", "name" => "Ana", "extra" => "yes", "bonus" => "3" }), "Ana has 1 item and 3 bonuses.".to_string());
+    assert_eq!(format(localization_vars!{ "count" => "1", "name" => "Ana" }), "Ana has 1 item.".to_string());
+
+    // `#` in a select block refers to the enclosing plural block
+    let message = "{count, plural, one {{gender, select, female {She has # file} other {They have # file}}} other {# files}}";
+    assert_eq!(msg_locator.format_message(&en, message, &localization_vars!{ "count" => "1", "gender" => "female" }), "She has 1 file".to_string());
+    let ru = parse_locale("ru").unwrap();
+    let message = "{n, plural, one {# файл} few {# файла} many {# файлов} other {# файла}}";
+    assert_eq!(msg_locator.format_message(&ru, message, &localization_vars!{ "n" => "22" }), "22 файла".to_string());
+    assert_eq!(msg_locator.format_message(&ru, message, &localization_vars!{ "n" => "11" }), "11 файлов".to_string());
+
+    // escaped braces do not open a nested plural block
+    let msg_locator = MessageLocator::new(
+        MessageLocatorOptions::new()
+            .supported_locales(vec!["en"])
+            .default_locale("en")
+            .placeholder_style(PlaceholderStyle::Braces)
+    ); // msg_locator
+    let message = "{n, plural, other {{{n, plural, #}} # items}}";
+    assert_eq!(msg_locator.format_message(&en, message, &localization_vars!{ "n" => "2" }), "{n, plural, 2} 2 items".to_string());
 }
\ No newline at end of file